@@ -0,0 +1,295 @@
+//! Answers to puzzles, and recording them to check that solutions keep producing the same results.
+//!
+//! Every part of every day has an [`Answer`], whatever type its solution returns. The answers for
+//! an input are stored next to it, with `.answers` appended to its file name, e.g.
+//! `inputs/day07/input.answers`:
+//!
+//! ```text
+//! # comments and empty lines are ignored
+//! 1: 250957639
+//! 2: 251515496
+//! ```
+
+use crate::Part;
+use anyhow::{anyhow, bail, Context, Result};
+use num::BigInt;
+use std::convert::Infallible;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The answer to one part of a puzzle: an integer of any size, or some text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(BigInt),
+    Text(String),
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Self::Int(BigInt::from(n))
+                }
+            }
+        )*
+    };
+}
+
+answer_from_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl From<BigInt> for Answer {
+    fn from(n: BigInt) -> Self {
+        Self::Int(n)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Self::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Self::Text(s.to_owned())
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(n) => f.pad(&n.to_string()),
+            Self::Text(s) => f.pad(s),
+        }
+    }
+}
+
+impl FromStr for Answer {
+    type Err = Infallible;
+
+    /// An integer if `s` is written the way an integer is displayed, text otherwise.
+    ///
+    /// So `42` and `-7` are integers, but `+42`, `007` and `-0` are text, and every answer parses
+    /// back into itself.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix('-').unwrap_or(s);
+
+        let canonical = !digits.is_empty()
+            && digits.bytes().all(|b| b.is_ascii_digit())
+            && (digits == "0" || !digits.starts_with('0'))
+            && s != "-0";
+
+        match s.parse() {
+            Ok(n) if canonical => Ok(Self::Int(n)),
+            _ => Ok(Self::Text(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers {
+    one: Option<Answer>,
+    two: Option<Answer>,
+}
+
+impl Answers {
+    /// Where the answers for the input at `input` are stored.
+    pub fn path_for<P>(input: P) -> PathBuf
+    where
+        P: AsRef<Path>,
+    {
+        let mut path = input.as_ref().as_os_str().to_owned();
+        path.push(".answers");
+        PathBuf::from(path)
+    }
+
+    /// Load answers from `path`, if the file exists.
+    pub fn load<P>(path: P) -> Result<Option<Self>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("could not read {}", path.display())),
+        };
+
+        let answers = content
+            .parse()
+            .with_context(|| format!("invalid answers file {}", path.display()))?;
+
+        Ok(Some(answers))
+    }
+
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_string())
+            .with_context(|| format!("could not write {}", path.display()))
+    }
+
+    pub fn get(&self, part: Part) -> Option<&Answer> {
+        match part {
+            Part::One => self.one.as_ref(),
+            Part::Two => self.two.as_ref(),
+        }
+    }
+
+    pub fn set(&mut self, part: Part, answer: Answer) {
+        match part {
+            Part::One => self.one = Some(answer),
+            Part::Two => self.two = Some(answer),
+        }
+    }
+
+    /// Compare `answer` to the recorded answer for `part`.
+    pub fn check(&self, part: Part, answer: &Answer) -> Status {
+        match self.get(part) {
+            Some(expected) if expected == answer => Status::Pass,
+            Some(expected) => Status::Fail {
+                expected: expected.clone(),
+            },
+            None => Status::Missing,
+        }
+    }
+}
+
+impl FromStr for Answers {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut answers = Self::default();
+
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (part, answer) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("line {}: expected `<part>: <answer>`", idx + 1))?;
+
+            let part = match part.trim() {
+                "1" => Part::One,
+                "2" => Part::Two,
+                other => bail!("line {}: not a part: {other}", idx + 1),
+            };
+
+            let Ok(answer) = answer.trim().parse();
+            answers.set(part, answer);
+        }
+
+        Ok(answers)
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in Part::BOTH {
+            if let Some(answer) = self.get(part) {
+                writeln!(f, "{part}: {answer}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Result of comparing an answer to the recorded one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail { expected: Answer },
+    Missing,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pass => f.pad("pass"),
+            Self::Fail { .. } => f.pad("FAIL"),
+            Self::Missing => f.pad("missing"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(s: &str) -> Answer {
+        let Ok(answer) = s.parse();
+        answer
+    }
+
+    #[test]
+    fn answer_types() {
+        assert_eq!(Answer::from(42u8), Answer::from(42i128));
+        assert_eq!(Answer::from(usize::MAX), answer(&usize::MAX.to_string()));
+        assert_eq!(Answer::from(-5i32).to_string(), "-5");
+        assert_eq!(answer("-5"), Answer::from(-5i64));
+
+        let huge = "123456789012345678901234567890123456789012345678901234567890";
+        assert!(matches!(answer(huge), Answer::Int(_)));
+        assert_eq!(answer(huge).to_string(), huge);
+
+        for text in ["", "abc", "+42", "007", "-0", "-", "1 2", "RXQ"] {
+            assert_eq!(answer(text), Answer::from(text));
+            assert_eq!(answer(text).to_string(), text);
+        }
+
+        assert_ne!(answer("12"), Answer::from("12"));
+        assert_eq!(format!("{:>5}", Answer::from(12u64)), "   12");
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut answers = Answers::default();
+        answers.set(Part::One, Answer::from(1234u32));
+        answers.set(Part::Two, Answer::from(-5i32));
+
+        let text = answers.to_string();
+        assert_eq!(text, "1: 1234\n2: -5\n");
+        assert_eq!(text.parse::<Answers>().unwrap(), answers);
+    }
+
+    #[test]
+    fn parse() {
+        let answers = "# day 7\n\n2:  42 \n".parse::<Answers>().unwrap();
+        assert_eq!(answers.get(Part::One), None);
+        assert_eq!(answers.get(Part::Two), Some(&Answer::from(42u64)));
+
+        assert!("3: 42".parse::<Answers>().is_err());
+        assert!("42".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn check() {
+        let answers = "1: 42".parse::<Answers>().unwrap();
+
+        assert_eq!(answers.check(Part::One, &Answer::from(42u32)), Status::Pass);
+        assert_eq!(
+            answers.check(Part::One, &Answer::from(43u32)),
+            Status::Fail {
+                expected: Answer::from(42u32)
+            }
+        );
+        assert_eq!(answers.check(Part::Two, &answer("42")), Status::Missing);
+    }
+
+    #[test]
+    fn path_for() {
+        assert_eq!(
+            Answers::path_for("inputs/day07/input"),
+            PathBuf::from("inputs/day07/input.answers")
+        );
+    }
+}
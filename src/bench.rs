@@ -0,0 +1,294 @@
+//! A small benchmark harness, timing parsing and each part separately.
+//!
+//! With the `alloc-stats` feature, the allocations of the first run of each stage are counted too,
+//! see [`crate::memory`].
+
+use crate::error::Unsolved;
+use crate::memory::{self, AllocStats, Bytes};
+use crate::{Day, Part};
+use anyhow::{ensure, Context, Result};
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Summary statistics over the durations of repeated runs.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        assert!(!samples.is_empty(), "no samples");
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+
+        let n = sorted.len();
+
+        let median = if n.is_multiple_of(2) {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        } else {
+            sorted[n / 2]
+        };
+
+        let mean = sorted.iter().map(Duration::as_secs_f64).sum::<f64>() / n as f64;
+
+        // sample standard deviation, which is zero for a single run
+        let variance = if n > 1 {
+            sorted
+                .iter()
+                .map(|d| (d.as_secs_f64() - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1) as f64
+        } else {
+            0.0
+        };
+
+        Self {
+            runs: n,
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// What was timed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stage {
+    Parse,
+    Part(Part),
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse => f.pad("parse"),
+            Self::Part(part) => f.pad(&format!("part {part}")),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Measurement {
+    pub day: u8,
+    pub stage: Stage,
+    pub stats: Stats,
+
+    /// Allocations of the first run, if they are counted.
+    pub memory: Option<AllocStats>,
+}
+
+fn time<F, T>(runs: usize, mut f: F) -> Result<(Stats, Option<AllocStats>)>
+where
+    F: FnMut() -> Result<T>,
+{
+    let mut samples = Vec::with_capacity(runs);
+    let mut memory = None;
+
+    for run in 0..runs {
+        let start = Instant::now();
+        let (result, allocs) = if run == 0 {
+            memory::measure(&mut f)
+        } else {
+            (f(), None)
+        };
+        samples.push(start.elapsed());
+
+        // keep the work from being optimized away, and drop the result outside of the timing
+        black_box(result?);
+        memory = memory.or(allocs);
+    }
+
+    Ok((Stats::from_samples(&samples), memory))
+}
+
+/// Time parsing `input` and solving each of `parts`, `runs` times each.
+///
+/// Parts that aren't solved yet are left out, see [`Unsolved`].
+pub fn bench(day: &Day, input: &[u8], parts: &[Part], runs: usize) -> Result<Vec<Measurement>> {
+    ensure!(runs > 0, "need at least one run");
+
+    let mut measurements = Vec::new();
+
+    let (stats, memory) = time(runs, || day.parse(input)).context("parse")?;
+    measurements.push(Measurement {
+        day: day.number,
+        stage: Stage::Parse,
+        stats,
+        memory,
+    });
+
+    let puzzle = day.parse(input)?;
+
+    for &part in parts {
+        let (stats, memory) = match time(runs, || puzzle.solve(part)) {
+            Err(e) if Unsolved::is(&e) => continue,
+            result => result.with_context(|| format!("part {part}"))?,
+        };
+        measurements.push(Measurement {
+            day: day.number,
+            stage: Stage::Part(part),
+            stats,
+            memory,
+        });
+    }
+
+    Ok(measurements)
+}
+
+/// Write measurements as a human-readable table, with allocations if they were counted.
+pub fn write_table<W>(mut w: W, measurements: &[Measurement]) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    let counted = measurements.iter().any(|m| m.memory.is_some());
+
+    write!(
+        w,
+        "{:>3}  {:<6}  {:>5}  {:>10}  {:>10}  {:>10}  {:>10}",
+        "day", "stage", "runs", "min", "median", "mean", "stddev"
+    )?;
+
+    if counted {
+        write!(w, "  {:>9}  {:>10}  {:>10}", "allocs", "allocated", "peak")?;
+    }
+
+    writeln!(w)?;
+
+    for m in measurements {
+        let s = &m.stats;
+        write!(
+            w,
+            "{:>3}  {:<6}  {:>5}  {:>10}  {:>10}  {:>10}  {:>10}",
+            m.day,
+            m.stage,
+            s.runs,
+            format!("{:.2?}", s.min),
+            format!("{:.2?}", s.median),
+            format!("{:.2?}", s.mean),
+            format!("{:.2?}", s.stddev),
+        )?;
+
+        if let Some(a) = &m.memory {
+            write!(
+                w,
+                "  {:>9}  {:>10}  {:>10}",
+                a.allocations,
+                Bytes(a.bytes),
+                Bytes(a.peak)
+            )?;
+        }
+
+        writeln!(w)?;
+    }
+
+    Ok(())
+}
+
+/// Write measurements as a JSON array, with all durations in nanoseconds.
+///
+/// Each element looks like
+/// `{"day": 6, "stage": "part 2", "runs": 10, "min_ns": 1, "median_ns": 2, "mean_ns": 2, "stddev_ns": 0}`,
+/// with `"allocations"`, `"allocated_bytes"` and `"peak_bytes"` added if allocations were counted.
+pub fn write_json<W>(mut w: W, measurements: &[Measurement]) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    writeln!(w, "[")?;
+
+    for (idx, m) in measurements.iter().enumerate() {
+        let s = &m.stats;
+        let comma = if idx + 1 < measurements.len() {
+            ","
+        } else {
+            ""
+        };
+
+        write!(
+            w,
+            "  {{\"day\": {}, \"stage\": \"{}\", \"runs\": {}, \"min_ns\": {}, \"median_ns\": {}, \
+             \"mean_ns\": {}, \"stddev_ns\": {}",
+            m.day,
+            m.stage,
+            s.runs,
+            s.min.as_nanos(),
+            s.median.as_nanos(),
+            s.mean.as_nanos(),
+            s.stddev.as_nanos(),
+        )?;
+
+        if let Some(a) = &m.memory {
+            write!(
+                w,
+                ", \"allocations\": {}, \"allocated_bytes\": {}, \"peak_bytes\": {}",
+                a.allocations, a.bytes, a.peak
+            )?;
+        }
+
+        writeln!(w, "}}{comma}")?;
+    }
+
+    writeln!(w, "]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn stats() {
+        let stats = Stats::from_samples(&[ms(4), ms(2), ms(6), ms(8)]);
+
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.min, ms(2));
+        assert_eq!(stats.median, ms(5));
+        assert_eq!(stats.mean, ms(5));
+
+        // sample variance is (9 + 1 + 1 + 9) / 3
+        let expected = (20.0f64 / 3.0).sqrt() / 1000.0;
+        assert!((stats.stddev.as_secs_f64() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn single_sample() {
+        let stats = Stats::from_samples(&[ms(3)]);
+
+        assert_eq!(stats.median, ms(3));
+        assert_eq!(stats.stddev, Duration::ZERO);
+    }
+
+    #[test]
+    fn bench_day() {
+        let day = crate::day(6).unwrap();
+        let input = b"Time:      7  15   30\nDistance:  9  40  200\n";
+
+        let measurements = bench(day, input, &Part::BOTH, 3).unwrap();
+
+        let stages = measurements.iter().map(|m| m.stage).collect::<Vec<_>>();
+        assert_eq!(
+            stages,
+            [Stage::Parse, Stage::Part(Part::One), Stage::Part(Part::Two)]
+        );
+        assert!(measurements.iter().all(|m| m.stats.runs == 3));
+        assert!(measurements
+            .iter()
+            .all(|m| m.memory.is_some() == memory::enabled()));
+
+        let mut json = Vec::new();
+        write_json(&mut json, &measurements).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.starts_with("[\n  {\"day\": 6, \"stage\": \"parse\", \"runs\": 3,"));
+        assert!(json.ends_with("}\n]\n"));
+    }
+}
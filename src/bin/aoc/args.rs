@@ -0,0 +1,618 @@
+use crate::report::ReportFormat;
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use aoc_2023::generate::{self, Knobs};
+use aoc_2023::input::Source;
+use aoc_2023::render::Format;
+use aoc_2023::Part;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage:
+    aoc run <day>|all [--part 1|2] [--input <path>|-] [--jobs <n>] [--render ansi|ppm|svg] [--stream]
+                      [--format table|json|csv|markdown] [--no-cache]
+    aoc check [<day>|all] [--part 1|2] [--input <path>] [--record]
+    aoc bench <day>|all [--part 1|2] [--input <path>|-] [--runs <n>] [--format table|json]
+    aoc generate <day>|all [--seed <n>] [--set <knob>=<n>]... [--output <path>|-] [--force] [--knobs]
+    aoc watch <day> [--part 1|2] [--input <path>]
+    aoc new <day>
+    aoc cache clear [<day>|all]
+    aoc help
+
+Inputs are read from $AOC_INPUTS/day<n>/input, or inputs/day<n>/input if AOC_INPUTS is not set.
+--input reads a single day's input from a path, or from stdin if given '-'.
+
+run solves days in parallel, on --jobs threads (default: one per core), and prints a summary when
+running all days. --render also draws the days that can be drawn (3, 10 and 11): as coloured text
+after the answers, or as images in day<n>.ppm or day<n>.svg files in the current directory.
+--stream solves the days that can be (1, 2, 4, 9 and 12) while reading their input line by line,
+for inputs too big to fit in memory. Both parts are solved at once, and timed together.
+--format prints a report for scripts instead of the table: JSON, CSV or a Markdown table, with the
+day, part, status (ok or error), answer, duration_ns, input and error of each part. Answers are
+always strings, and fields may be added at the end, but never change.
+
+run caches answers in $AOC_CACHE, or .aoc-cache if AOC_CACHE is not set, keyed by day, part, a hash
+of the input and the version of the solver, and answers from the cache instantly when it can.
+--no-cache neither reads nor writes the cache. Days that are streamed are never cached. When a
+day's answers change, bump its Solution::VERSION, or clear its answers with `aoc cache clear`.
+
+check compares answers to those recorded next to the input, e.g. in inputs/day07/input.answers.
+--record saves the current answers instead.
+
+bench times parsing and each part separately, repeating each --runs times (default 10).
+
+When aoc is built with --features alloc-stats, run and bench also show how many allocations parsing
+and each part make, how many bytes they allocate, and how much the heap grows at most.
+
+Answers are computed in 64 bits, and a part fails if its answer doesn't fit. When built with
+--features wide, they are computed in 128 bits, and generate allows larger inputs.
+
+generate writes a random input of a day, to stdout or to --output. For all days, --output is a
+directory to write day<n>/input files to. The size of inputs is set with knobs, listed by --knobs.
+Existing files are only overwritten with --force.
+
+watch solves a day for its input and examples, and again whenever they change, showing how the
+answers changed. When the day's source changes, aoc is rebuilt with cargo and restarted.
+
+new adds a day to this crate: src/day<n>.rs with a stub solution and a test of its example, an
+empty example in inputs/day<n>/examples/, and the day in src/lib.rs. Existing days are left alone.";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Selection {
+    All,
+    Day(u8),
+}
+
+/// Which days and parts to solve, and from what input.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Target {
+    pub selection: Selection,
+    pub part: Option<Part>,
+    pub input: Option<Source>,
+}
+
+impl Target {
+    pub fn parts(&self) -> Vec<Part> {
+        match self.part {
+            Some(part) => vec![part],
+            None => Part::BOTH.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Help,
+    Run {
+        target: Target,
+        jobs: Option<usize>,
+        render: Option<Format>,
+        stream: bool,
+        /// The format of a report for scripts, instead of the table.
+        format: Option<ReportFormat>,
+        /// Whether to use the cache, i.e. unless `--no-cache` is given.
+        cache: bool,
+    },
+    Check {
+        target: Target,
+        record: bool,
+    },
+    Bench {
+        target: Target,
+        runs: usize,
+        format: BenchFormat,
+    },
+    Generate {
+        selection: Selection,
+        seed: u64,
+        knobs: Knobs,
+        output: Option<PathBuf>,
+        force: bool,
+        list_knobs: bool,
+    },
+    Watch {
+        target: Target,
+    },
+    New {
+        day: u8,
+    },
+    ClearCache {
+        selection: Selection,
+    },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BenchFormat {
+    Table,
+    Json,
+}
+
+fn parse_selection(s: &str) -> Result<Selection> {
+    if s == "all" {
+        return Ok(Selection::All);
+    }
+
+    let day = s.parse::<u8>().with_context(|| format!("not a day: {s}"))?;
+
+    Ok(Selection::Day(day))
+}
+
+fn parse_part(s: &str) -> Result<Part> {
+    match s {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        other => Err(anyhow!("not a part: {other}")),
+    }
+}
+
+/// Remaining arguments of a command.
+struct Args<I> {
+    args: I,
+}
+
+impl<I> Args<I>
+where
+    I: Iterator<Item = String>,
+{
+    fn value(&mut self, flag: &str) -> Result<String> {
+        self.args
+            .next()
+            .with_context(|| format!("{flag} needs a value"))
+    }
+
+    /// Parse the target of a command.
+    ///
+    /// Any option that is not part of the target is passed to `other`, which returns `false` if it
+    /// does not know the option either.
+    fn target<F>(mut self, default: Option<Selection>, mut other: F) -> Result<Target>
+    where
+        F: FnMut(&str, &mut Self) -> Result<bool>,
+    {
+        let mut selection = None;
+        let mut part = None;
+        let mut input = None;
+
+        while let Some(arg) = self.args.next() {
+            match arg.as_str() {
+                "-p" | "--part" => part = Some(parse_part(&self.value(&arg)?)?),
+                "-i" | "--input" => input = Some(Source::from(self.value(&arg)?.as_str())),
+
+                flag if flag.starts_with('-') => {
+                    if !other(flag, &mut self)? {
+                        bail!("unknown option: {flag}");
+                    }
+                }
+
+                day if selection.is_none() => selection = Some(parse_selection(day)?),
+                other => bail!("unexpected argument: {other}"),
+            }
+        }
+
+        let selection = selection.or(default).context("no day given")?;
+
+        if selection == Selection::All && input.is_some() {
+            bail!("--input can only be used with a single day");
+        }
+
+        Ok(Target {
+            selection,
+            part,
+            input,
+        })
+    }
+}
+
+pub fn parse<I>(args: I) -> Result<Command>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+
+    let command = match args.next() {
+        Some(command) => command,
+        None => return Ok(Command::Help),
+    };
+
+    let args = Args { args };
+
+    match command.as_str() {
+        "help" | "-h" | "--help" => Ok(Command::Help),
+
+        "run" => {
+            let mut jobs = None;
+            let mut render = None;
+            let mut stream = false;
+            let mut format = None;
+            let mut cache = true;
+
+            let target = args.target(None, |flag, args| match flag {
+                "-j" | "--jobs" => {
+                    let value = args.value(flag)?;
+                    let n = value
+                        .parse()
+                        .with_context(|| format!("not a number of jobs: {value}"))?;
+                    ensure!(n > 0, "need at least one job");
+                    jobs = Some(n);
+                    Ok(true)
+                }
+
+                "--render" => {
+                    render = Some(args.value(flag)?.parse()?);
+                    Ok(true)
+                }
+
+                "--stream" => {
+                    stream = true;
+                    Ok(true)
+                }
+
+                "-f" | "--format" => {
+                    format = match args.value(flag)?.as_str() {
+                        "table" => None,
+                        other => Some(other.parse()?),
+                    };
+                    Ok(true)
+                }
+
+                "--no-cache" => {
+                    cache = false;
+                    Ok(true)
+                }
+
+                _ => Ok(false),
+            })?;
+
+            if format.is_some() && render.is_some() {
+                bail!("--render can only be used with the table, not a report");
+            }
+
+            Ok(Command::Run {
+                target,
+                jobs,
+                render,
+                stream,
+                format,
+                cache,
+            })
+        }
+
+        "check" => {
+            let mut record = false;
+
+            let target = args.target(Some(Selection::All), |flag, _| match flag {
+                "--record" => {
+                    record = true;
+                    Ok(true)
+                }
+
+                _ => Ok(false),
+            })?;
+
+            if target.input == Some(Source::Stdin) {
+                bail!("answers can not be checked for stdin");
+            }
+
+            Ok(Command::Check { target, record })
+        }
+
+        "bench" => {
+            let mut runs = 10;
+            let mut format = BenchFormat::Table;
+
+            let target = args.target(None, |flag, args| match flag {
+                "-n" | "--runs" => {
+                    let value = args.value(flag)?;
+                    runs = value
+                        .parse()
+                        .with_context(|| format!("not a number of runs: {value}"))?;
+                    ensure!(runs > 0, "need at least one run");
+                    Ok(true)
+                }
+
+                "-f" | "--format" => {
+                    format = match args.value(flag)?.as_str() {
+                        "table" => BenchFormat::Table,
+                        "json" => BenchFormat::Json,
+                        other => bail!("unknown format: {other}"),
+                    };
+                    Ok(true)
+                }
+
+                _ => Ok(false),
+            })?;
+
+            Ok(Command::Bench {
+                target,
+                runs,
+                format,
+            })
+        }
+
+        "generate" => {
+            let mut seed = generate::DEFAULT_SEED;
+            let mut knobs = Knobs::new();
+            let mut output = None;
+            let mut force = false;
+            let mut list_knobs = false;
+
+            let target = args.target(None, |flag, args| match flag {
+                "--seed" => {
+                    let value = args.value(flag)?;
+                    seed = value
+                        .parse()
+                        .with_context(|| format!("not a seed: {value}"))?;
+                    Ok(true)
+                }
+
+                "-s" | "--set" => {
+                    let value = args.value(flag)?;
+                    let (name, n) = value
+                        .split_once('=')
+                        .with_context(|| format!("not a knob setting like `width=100`: {value}"))?;
+                    let n = n
+                        .parse()
+                        .with_context(|| format!("not a number for knob {name}: {n}"))?;
+                    knobs.set(name, n);
+                    Ok(true)
+                }
+
+                "-o" | "--output" => {
+                    output = match args.value(flag)?.as_str() {
+                        "-" => None,
+                        path => Some(PathBuf::from(path)),
+                    };
+                    Ok(true)
+                }
+
+                "--force" => {
+                    force = true;
+                    Ok(true)
+                }
+
+                "--knobs" => {
+                    list_knobs = true;
+                    Ok(true)
+                }
+
+                _ => Ok(false),
+            })?;
+
+            if target.part.is_some() || target.input.is_some() {
+                bail!("generate doesn't take --part or --input");
+            }
+
+            Ok(Command::Generate {
+                selection: target.selection,
+                seed,
+                knobs,
+                output,
+                force,
+                list_knobs,
+            })
+        }
+
+        "watch" => {
+            let target = args.target(None, |_, _| Ok(false))?;
+
+            if target.selection == Selection::All {
+                bail!("watch needs a single day");
+            }
+
+            if target.input == Some(Source::Stdin) {
+                bail!("stdin can not be watched");
+            }
+
+            Ok(Command::Watch { target })
+        }
+
+        "new" => {
+            let target = args.target(None, |_, _| Ok(false))?;
+
+            let Selection::Day(day) = target.selection else {
+                bail!("new needs a single day");
+            };
+
+            if target.part.is_some() || target.input.is_some() {
+                bail!("new doesn't take --part or --input");
+            }
+
+            ensure!((1..=25).contains(&day), "not a day of Advent: {day}");
+
+            Ok(Command::New { day })
+        }
+
+        "cache" => {
+            let mut args = args;
+
+            match args.args.next().as_deref() {
+                Some("clear") => {}
+                Some(other) => bail!("unknown cache command: {other}"),
+                None => bail!("cache needs a command: clear"),
+            }
+
+            let target = args.target(Some(Selection::All), |_, _| Ok(false))?;
+
+            if target.part.is_some() || target.input.is_some() {
+                bail!("cache clear doesn't take --part or --input");
+            }
+
+            Ok(Command::ClearCache {
+                selection: target.selection,
+            })
+        }
+
+        other => Err(anyhow!("unknown command: {other}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(s: &str) -> Result<Command> {
+        parse(s.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn run() {
+        assert_eq!(
+            parse_str("run 7 --part 2 -i -").unwrap(),
+            Command::Run {
+                target: Target {
+                    selection: Selection::Day(7),
+                    part: Some(Part::Two),
+                    input: Some(Source::Stdin),
+                },
+                jobs: None,
+                render: None,
+                stream: false,
+                format: None,
+                cache: true,
+            }
+        );
+
+        assert_eq!(
+            parse_str("run all -j 3 --render svg --stream --no-cache").unwrap(),
+            Command::Run {
+                target: Target {
+                    selection: Selection::All,
+                    part: None,
+                    input: None,
+                },
+                jobs: Some(3),
+                render: Some(Format::Svg),
+                stream: true,
+                format: None,
+                cache: false,
+            }
+        );
+
+        assert_eq!(
+            parse_str("run 7 --format csv --format table -f json").unwrap(),
+            Command::Run {
+                target: Target {
+                    selection: Selection::Day(7),
+                    part: None,
+                    input: None,
+                },
+                jobs: None,
+                render: None,
+                stream: false,
+                format: Some(ReportFormat::Json),
+                cache: true,
+            }
+        );
+
+        assert!(parse_str("run").is_err());
+        assert!(parse_str("run all --input foo").is_err());
+        assert!(parse_str("run 7 --record").is_err());
+        assert!(parse_str("run all --jobs 0").is_err());
+        assert!(parse_str("run 10 --render png").is_err());
+        assert!(parse_str("run 10 --format xml").is_err());
+        assert!(parse_str("run 10 --format json --render svg").is_err());
+    }
+
+    #[test]
+    fn check() {
+        assert_eq!(
+            parse_str("check --record").unwrap(),
+            Command::Check {
+                target: Target {
+                    selection: Selection::All,
+                    part: None,
+                    input: None,
+                },
+                record: true,
+            }
+        );
+
+        assert!(parse_str("check 3 -i -").is_err());
+    }
+
+    #[test]
+    fn bench() {
+        assert_eq!(
+            parse_str("bench all --runs 3 --format json").unwrap(),
+            Command::Bench {
+                target: Target {
+                    selection: Selection::All,
+                    part: None,
+                    input: None,
+                },
+                runs: 3,
+                format: BenchFormat::Json,
+            }
+        );
+
+        assert!(parse_str("bench 3 --runs 0").is_err());
+        assert!(parse_str("bench 3 --format xml").is_err());
+    }
+
+    #[test]
+    fn generate() {
+        let mut knobs = Knobs::new();
+        knobs.set("width", 20);
+        knobs.set("height", 10);
+
+        assert_eq!(
+            parse_str("generate 11 --seed 7 -s width=20 --set height=10 -o inputs/day11/big")
+                .unwrap(),
+            Command::Generate {
+                selection: Selection::Day(11),
+                seed: 7,
+                knobs,
+                output: Some(PathBuf::from("inputs/day11/big")),
+                force: false,
+                list_knobs: false,
+            }
+        );
+
+        assert!(parse_str("generate 11 --set width").is_err());
+        assert!(parse_str("generate 11 --part 1").is_err());
+    }
+
+    #[test]
+    fn watch() {
+        assert_eq!(
+            parse_str("watch 7 -p 1").unwrap(),
+            Command::Watch {
+                target: Target {
+                    selection: Selection::Day(7),
+                    part: Some(Part::One),
+                    input: None,
+                },
+            }
+        );
+
+        assert!(parse_str("watch all").is_err());
+        assert!(parse_str("watch 7 --input -").is_err());
+    }
+
+    #[test]
+    fn new() {
+        assert_eq!(parse_str("new 13").unwrap(), Command::New { day: 13 });
+
+        assert!(parse_str("new all").is_err());
+        assert!(parse_str("new 26").is_err());
+        assert!(parse_str("new 13 -p 1").is_err());
+    }
+
+    #[test]
+    fn cache() {
+        assert_eq!(
+            parse_str("cache clear").unwrap(),
+            Command::ClearCache {
+                selection: Selection::All
+            }
+        );
+        assert_eq!(
+            parse_str("cache clear 7").unwrap(),
+            Command::ClearCache {
+                selection: Selection::Day(7)
+            }
+        );
+
+        assert!(parse_str("cache").is_err());
+        assert!(parse_str("cache show").is_err());
+        assert!(parse_str("cache clear 7 -p 1").is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use crate::args::{BenchFormat, Target};
+use crate::run;
+use anyhow::Result;
+use aoc_2023::bench::{self, Measurement};
+use aoc_2023::input::Source;
+
+/// Benchmark all targeted days, printing the measurements to stdout.
+///
+/// Returns `false` if any day could not be benchmarked.
+pub fn bench(target: &Target, runs: usize, format: BenchFormat) -> Result<bool> {
+    let parts = target.parts();
+    let mut measurements: Vec<Measurement> = Vec::new();
+    let mut ok = true;
+
+    for day in run::days(target.selection)? {
+        let source = run::source(target, day);
+
+        if let Source::File(path) = &source {
+            if target.input.is_none() && !path.exists() {
+                eprintln!("day {}: skipped, no input", day.number);
+                continue;
+            }
+        }
+
+        let result = source
+            .read()
+            .and_then(|input| bench::bench(day, &input, &parts, runs));
+
+        match result {
+            Ok(m) => measurements.extend(m),
+            Err(e) => {
+                eprintln!("day {}: {e:#}", day.number);
+                ok = false;
+            }
+        }
+    }
+
+    let stdout = std::io::stdout().lock();
+
+    match format {
+        BenchFormat::Table => bench::write_table(stdout, &measurements)?,
+        BenchFormat::Json => bench::write_json(stdout, &measurements)?,
+    }
+
+    Ok(ok)
+}
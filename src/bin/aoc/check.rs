@@ -0,0 +1,99 @@
+use crate::args::Target;
+use crate::run::{self, Row};
+use anyhow::Result;
+use aoc_2023::answers::{Answers, Status};
+use aoc_2023::input::Source;
+
+/// Print how `row` compares to `answers`, returning `true` if it failed.
+///
+/// Parts that aren't solved yet don't fail, like parts without a recorded answer.
+fn report(row: &Row, answers: &Answers) -> bool {
+    let (day, part) = (row.day, row.part);
+
+    match &row.answer {
+        Ok(answer) => {
+            let status = answers.check(part, answer);
+            match &status {
+                Status::Fail { expected } => {
+                    println!("{day:>3}  {part:>4}  {status:<7}  {answer} (expected {expected})")
+                }
+                _ => println!("{day:>3}  {part:>4}  {status:<7}  {answer}"),
+            }
+
+            status != Status::Pass && status != Status::Missing
+        }
+
+        Err(_) if row.unsolved() => {
+            println!("{day:>3}  {part:>4}  unsolved");
+            false
+        }
+
+        Err(e) => {
+            println!("{day:>3}  {part:>4}  {:<7}  {e:#}", "error");
+            true
+        }
+    }
+}
+
+/// Check the answers of all targeted days against the recorded answers, or record them.
+///
+/// Returns `false` if any answer did not match or could not be computed. Parts that aren't solved
+/// yet are neither checked nor recorded.
+pub fn check(target: &Target, record: bool) -> Result<bool> {
+    let parts = target.parts();
+    let mut ok = true;
+
+    println!("{:>3}  {:>4}  {:<7}  answer", "day", "part", "status");
+
+    for day in run::days(target.selection)? {
+        let input = match run::source(target, day) {
+            Source::File(path) => path,
+            Source::Stdin => unreachable!("stdin has no answers"),
+        };
+
+        if target.input.is_none() && !input.exists() {
+            // not having an input for some day is fine when checking all of them
+            for part in parts.iter() {
+                println!("{:>3}  {part:>4}  no input", day.number);
+            }
+
+            continue;
+        }
+
+        let answers_path = Answers::path_for(&input);
+        let mut answers = Answers::load(&answers_path)?.unwrap_or_default();
+
+        let rows = run::solve(day, &Source::File(input.clone()), &parts);
+
+        if record {
+            for row in rows.iter() {
+                match &row.answer {
+                    Ok(answer) => {
+                        answers.set(row.part, answer.clone());
+                        println!("{:>3}  {:>4}  recorded {answer}", row.day, row.part);
+                    }
+
+                    Err(_) if row.unsolved() => {
+                        println!("{:>3}  {:>4}  unsolved", row.day, row.part);
+                    }
+
+                    Err(e) => {
+                        println!("{:>3}  {:>4}  error: {e:#}", row.day, row.part);
+                        ok = false;
+                    }
+                }
+            }
+
+            answers.save(&answers_path)?;
+            continue;
+        }
+
+        for row in rows.iter() {
+            if report(row, &answers) {
+                ok = false;
+            }
+        }
+    }
+
+    Ok(ok)
+}
@@ -0,0 +1,101 @@
+use crate::args::Selection;
+use anyhow::{bail, Context, Result};
+use aoc_2023::generate::{self, Generator, Knobs, GENERATORS};
+use aoc_2023::input::Source;
+use std::path::Path;
+
+fn generators(selection: Selection) -> Result<Vec<&'static Generator>> {
+    match selection {
+        Selection::All => Ok(GENERATORS.iter().collect()),
+        Selection::Day(n) => {
+            let g = generate::generator(n).with_context(|| format!("no generator for day {n}"))?;
+            Ok(vec![g])
+        }
+    }
+}
+
+/// Print the knobs of the selected days, with their defaults.
+pub fn list_knobs(selection: Selection) -> Result<()> {
+    println!("{:>3}  {:<12}  {:>7}  about", "day", "knob", "default");
+
+    for g in generators(selection)? {
+        for knob in g.knobs {
+            println!(
+                "{:>3}  {:<12}  {:>7}  {}",
+                g.day, knob.name, knob.default, knob.about
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn write(path: &Path, input: &str, force: bool) -> Result<()> {
+    if !force && path.exists() {
+        bail!(
+            "{} already exists, use --force to overwrite it",
+            path.display()
+        );
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("could not create {}", dir.display()))?;
+    }
+
+    std::fs::write(path, input).with_context(|| format!("could not write {}", path.display()))
+}
+
+/// Generate inputs for the selected days from `seed`.
+///
+/// A single day's input goes to `output`, or stdout. Inputs of all days go to `day<n>/input` in
+/// the `output` directory, where each day uses those `knobs` it has.
+pub fn generate(
+    selection: Selection,
+    seed: u64,
+    knobs: &Knobs,
+    output: Option<&Path>,
+    force: bool,
+) -> Result<()> {
+    if let Selection::Day(n) = selection {
+        let g = generate::generator(n).with_context(|| format!("no generator for day {n}"))?;
+        let input = g.generate(seed, knobs)?;
+
+        match output {
+            Some(path) => write(path, &input, force)?,
+            None => print!("{input}"),
+        }
+
+        return Ok(());
+    }
+
+    let dir = output.context("generating all days needs an --output directory")?;
+
+    if let Some(name) = knobs
+        .names()
+        .find(|name| !GENERATORS.iter().any(|g| g.has_knob(name)))
+    {
+        bail!("no day has a knob `{name}`");
+    }
+
+    for g in GENERATORS.iter() {
+        let mut own = Knobs::new();
+
+        for name in knobs.names().filter(|name| g.has_knob(name)) {
+            own.set(name, knobs.get(name));
+        }
+
+        let input = g
+            .generate(seed, &own)
+            .with_context(|| format!("day {}", g.day))?;
+
+        let Source::File(path) = Source::for_day_in(dir, g.day) else {
+            unreachable!("inputs of days are files");
+        };
+
+        write(&path, &input, force)?;
+        println!("wrote {}", path.display());
+    }
+
+    Ok(())
+}
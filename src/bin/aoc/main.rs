@@ -0,0 +1,141 @@
+//! Runner for all Advent of Code 2023 puzzles.
+//!
+//! Usage:
+//!
+//! ```text
+//! aoc run <day>|all [--part 1|2] [--input <path>|-] [--jobs <n>] [--render ansi|ppm|svg] [--stream]
+//!                   [--format table|json|csv|markdown] [--no-cache]
+//! aoc check [<day>|all] [--part 1|2] [--input <path>] [--record]
+//! aoc bench <day>|all [--part 1|2] [--input <path>|-] [--runs <n>] [--format table|json]
+//! aoc generate <day>|all [--seed <n>] [--set <knob>=<n>]... [--output <path>|-] [--force] [--knobs]
+//! aoc watch <day> [--part 1|2] [--input <path>]
+//! aoc new <day>
+//! aoc cache clear [<day>|all]
+//! ```
+
+mod args;
+mod bench;
+mod check;
+mod generate;
+mod new;
+mod report;
+mod run;
+mod watch;
+
+use aoc_2023::cache::Cache;
+use aoc_2023::pool;
+use args::{Command, Selection};
+use std::io;
+use std::process::ExitCode;
+use std::time::Instant;
+
+fn exit_code(ok: bool) -> ExitCode {
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn main() -> ExitCode {
+    let command = match args::parse(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            eprintln!();
+            eprintln!("{}", args::USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match command {
+        Command::Help => {
+            println!("{}", args::USAGE);
+            Ok(true)
+        }
+
+        Command::Run {
+            target,
+            jobs,
+            render,
+            stream,
+            format,
+            cache,
+        } => {
+            let start = Instant::now();
+            let jobs = jobs.unwrap_or_else(pool::default_workers);
+            let cache = cache.then(Cache::open);
+
+            run::run(&target, jobs, render.is_some(), stream, cache.as_ref()).and_then(|report| {
+                if let Some(format) = format {
+                    report::write(io::stdout().lock(), &report.rows, format)?;
+                    return Ok(report.rows.iter().all(|row| !row.failed()));
+                }
+
+                run::print_table(&report.rows);
+                run::print_memory(&report.memory);
+
+                if target.selection == Selection::All {
+                    run::print_summary(&report.rows, start.elapsed());
+                }
+
+                if let Some(format) = render {
+                    run::show(&report.drawings, format, target.selection)?;
+                }
+
+                Ok(report.rows.iter().all(|row| !row.failed()))
+            })
+        }
+
+        Command::Check { target, record } => check::check(&target, record),
+
+        Command::Bench {
+            target,
+            runs,
+            format,
+        } => bench::bench(&target, runs, format),
+
+        Command::Generate {
+            selection,
+            seed,
+            knobs,
+            output,
+            force,
+            list_knobs,
+        } => {
+            if list_knobs {
+                generate::list_knobs(selection).map(|_| true)
+            } else {
+                generate::generate(selection, seed, &knobs, output.as_deref(), force).map(|_| true)
+            }
+        }
+
+        Command::Watch { target } => watch::watch(&target).map(|_| true),
+
+        Command::New { day } => new::new(day).map(|_| true),
+
+        Command::ClearCache { selection } => {
+            let cache = Cache::open();
+            let day = match selection {
+                Selection::All => None,
+                Selection::Day(day) => Some(day),
+            };
+
+            cache.clear(day).map(|count| {
+                println!(
+                    "removed {count} cached answers from {}",
+                    cache.dir().display()
+                );
+                true
+            })
+        }
+    };
+
+    match result {
+        Ok(ok) => exit_code(ok),
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
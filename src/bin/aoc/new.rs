@@ -0,0 +1,276 @@
+use anyhow::{bail, ensure, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The module of a new day, with `{NN}` for its number.
+const MODULE: &str = r#"use crate::checked::Int;
+use crate::error::Unsolved;
+use crate::Solution;
+use anyhow::Result;
+use std::fmt;
+use std::io::BufRead;
+
+/// The puzzle input, line by line until there is something better to parse it into.
+#[derive(Debug)]
+pub struct Input {
+    pub lines: Vec<String>,
+}
+
+impl Solution for Input {
+    type PartOne = Int;
+    type PartTwo = Int;
+
+    fn parse<R>(reader: R) -> Result<Self>
+    where
+        R: BufRead,
+    {
+        let lines = reader.lines().collect::<Result<_, _>>()?;
+        Ok(Self { lines })
+    }
+
+    fn part_one(&self) -> Result<Int> {
+        Err(Unsolved.into())
+    }
+
+    fn part_two(&self) -> Result<Int> {
+        Err(Unsolved.into())
+    }
+}
+
+/// Writes the input back out, so that it parses the same again.
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in self.lines.iter() {
+            writeln!(f, "{line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static EXAMPLE: &str = include_str!("../inputs/day{NN}/examples/example");
+
+    #[test]
+    fn test_examples() {
+        let input = Input::parse(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(input.lines.len(), EXAMPLE.lines().count());
+
+        let text = input.to_string();
+        assert_eq!(Input::parse(text.as_bytes()).unwrap().lines, input.lines);
+    }
+}
+"#;
+
+/// The answers of the example, until they are known.
+const ANSWERS: &str = "\
+# from the puzzle description, e.g.
+# 1: 142
+";
+
+/// The number of a day in a `DAYS` entry like `    Day::new::<day06::Races>(6),`.
+fn entry_day(line: &str) -> Option<u8> {
+    let (_, n) = line.trim().strip_suffix("),")?.rsplit_once('(')?;
+    n.parse().ok()
+}
+
+/// The number of a day in a declaration like `pub mod day06;`.
+fn module_day(line: &str) -> Option<u8> {
+    line.strip_prefix("pub mod day")?
+        .strip_suffix(';')?
+        .parse()
+        .ok()
+}
+
+/// Add `day` to `lib`, the source of `lib.rs`: declare its module, and list it in `DAYS`.
+fn register(lib: &str, day: u8) -> Result<String> {
+    let mut lines = lib.lines().map(str::to_owned).collect::<Vec<_>>();
+
+    let modules = (0..lines.len())
+        .filter_map(|idx| Some((idx, module_day(&lines[idx])?)))
+        .collect::<Vec<_>>();
+
+    ensure!(!modules.is_empty(), "no day modules in lib.rs");
+    ensure!(
+        modules.iter().all(|&(_, n)| n != day),
+        "day {day} is already in lib.rs"
+    );
+
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("pub static DAYS: [Day; "))
+        .context("no DAYS in lib.rs")?;
+    let end = start
+        + lines[start..]
+            .iter()
+            .position(|line| line == "];")
+            .context("DAYS in lib.rs doesn't end")?;
+
+    // the entries go in order of their days, after those that come before `day`
+    let entry = (start + 1..end)
+        .rev()
+        .find(|&idx| entry_day(&lines[idx]).is_some_and(|n| n < day))
+        .map_or(start + 1, |idx| idx + 1);
+
+    let count = end - start - 1;
+    lines[start] = format!("pub static DAYS: [Day; {}] = [", count + 1);
+    lines.insert(entry, format!("    Day::new::<day{day:02}::Input>({day}),"));
+
+    // insert the module last, as it comes before DAYS
+    let module = modules
+        .iter()
+        .rev()
+        .find(|&&(_, n)| n < day)
+        .map_or(modules[0].0, |&(idx, _)| idx + 1);
+
+    lines.insert(module, format!("pub mod day{day:02};"));
+
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Write `contents` to `path`, unless there already is a file. Returns whether it was written.
+fn create(path: &Path, contents: &str) -> Result<bool> {
+    if path.exists() {
+        return Ok(false);
+    }
+
+    fs::write(path, contents).with_context(|| format!("could not write {}", path.display()))?;
+    Ok(true)
+}
+
+/// Add a module for `day` to the crate in `root`, list it in `DAYS`, and make an empty example
+/// for it. Returns the files that were created or changed.
+///
+/// Fails without changing anything if the day already has a module.
+pub fn scaffold(root: &Path, day: u8) -> Result<Vec<PathBuf>> {
+    let module = root.join("src").join(format!("day{day:02}.rs"));
+    let lib = root.join("src").join("lib.rs");
+
+    if module.exists() {
+        bail!("day {day} already exists in {}", module.display());
+    }
+
+    let source =
+        fs::read_to_string(&lib).with_context(|| format!("could not read {}", lib.display()))?;
+    let registered = register(&source, day)?;
+
+    let examples = root
+        .join("inputs")
+        .join(format!("day{day:02}"))
+        .join("examples");
+
+    fs::create_dir_all(&examples)
+        .with_context(|| format!("could not create {}", examples.display()))?;
+
+    let mut changed = Vec::new();
+
+    for (path, contents) in [
+        (examples.join("example"), ""),
+        (examples.join("example.answers"), ANSWERS),
+    ] {
+        if create(&path, contents)? {
+            changed.push(path);
+        }
+    }
+
+    let contents = MODULE.replace("{NN}", &format!("{day:02}"));
+    fs::write(&module, contents)
+        .with_context(|| format!("could not write {}", module.display()))?;
+    fs::write(&lib, registered).with_context(|| format!("could not write {}", lib.display()))?;
+
+    changed.push(module);
+    changed.push(lib);
+
+    Ok(changed)
+}
+
+/// Scaffold `day` in this crate, see [`scaffold`], and say what to do next.
+pub fn new(day: u8) -> Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    for path in scaffold(root, day)? {
+        let path = path.strip_prefix(root).unwrap_or(&path);
+        println!("wrote {}", path.display());
+    }
+
+    println!();
+    println!("next: paste the example into inputs/day{day:02}/examples/example, with its answers");
+    println!("next to it, and the input into inputs/day{day:02}/input. Then solve it with");
+    println!("`aoc watch {day}`, and add a generator to src/generate.rs.");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = "\
+pub mod answers;
+pub mod day01;
+pub mod day03;
+pub mod error;
+
+pub static DAYS: [Day; 2] = [
+    Day::streaming::<day01::Document>(1),
+    Day::new::<day03::EngineSchematic>(3),
+];
+";
+
+    #[test]
+    fn registering() {
+        let lib = register(LIB, 2).unwrap();
+
+        assert_eq!(
+            lib,
+            "\
+pub mod answers;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod error;
+
+pub static DAYS: [Day; 3] = [
+    Day::streaming::<day01::Document>(1),
+    Day::new::<day02::Input>(2),
+    Day::new::<day03::EngineSchematic>(3),
+];
+"
+        );
+
+        let lib = register(&lib, 13).unwrap();
+        assert!(lib.contains("pub mod day13;\npub mod error;"));
+        assert!(lib.contains("[Day; 4]"));
+        assert!(lib.contains("(3),\n    Day::new::<day13::Input>(13),\n];"));
+
+        assert!(register(LIB, 1).is_err());
+        assert!(register("pub mod answers;\n", 1).is_err());
+    }
+
+    #[test]
+    fn scaffolding() {
+        let root = std::env::temp_dir().join(format!("aoc-new-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src").join("lib.rs"), LIB).unwrap();
+
+        let changed = scaffold(&root, 2).unwrap();
+        assert_eq!(changed.len(), 4);
+
+        let module = fs::read_to_string(root.join("src").join("day02.rs")).unwrap();
+        assert!(module.contains("include_str!(\"../inputs/day02/examples/example\")"));
+        assert!(root.join("inputs/day02/examples/example.answers").exists());
+
+        // nothing is overwritten
+        let lib = fs::read_to_string(root.join("src").join("lib.rs")).unwrap();
+        assert!(scaffold(&root, 2).is_err());
+        assert_eq!(
+            fs::read_to_string(root.join("src").join("lib.rs")).unwrap(),
+            lib
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
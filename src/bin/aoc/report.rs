@@ -0,0 +1,299 @@
+//! Reports of `aoc run` for scripts, in JSON, CSV or as a Markdown table.
+//!
+//! A report has a record for each part that was run, in order of day and part, with these fields:
+//!
+//! - `day`: the number of the day
+//! - `part`: `1` or `2`
+//! - `status`: `ok` if the part was answered, `unsolved` if it isn't solved yet, `error` if it
+//!   couldn't be answered
+//! - `answer`: the answer, always as a string, as answers may be too big for JSON numbers
+//! - `duration_ns`: how long solving the part took in nanoseconds, not counting reading and
+//!   parsing the input, see [`Row::duration`]
+//! - `input`: the path the input was read from, or `-` for stdin
+//! - `error`: why the part wasn't answered
+//! - `cached`: `true` if the answer came from the cache, and `duration_ns` is how long looking it
+//!   up took, `false` otherwise
+//!
+//! In JSON, the report is an array of objects with these fields, in this order. `answer` is `null`
+//! unless the part was answered, `error` is `null` unless it couldn't be, and `cached` is a
+//! boolean. CSV has a header line with the field names, and leaves out answers and errors that
+//! aren't there. Fields are quoted as in RFC 4180 when needed, and lines end with `\n`. The
+//! Markdown table has the same columns, with the duration as readable `time` and only the first
+//! line of each error.
+//!
+//! Fields may be added at the end, but not removed, renamed or reordered.
+
+use crate::run::Row;
+use anyhow::{bail, Result};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "markdown" => Ok(Self::Markdown),
+            other => bail!("unknown report format: {other}"),
+        }
+    }
+}
+
+const FIELDS: [&str; 8] = [
+    "day",
+    "part",
+    "status",
+    "answer",
+    "duration_ns",
+    "input",
+    "error",
+    "cached",
+];
+
+/// The fields of a row, in the order of [`FIELDS`], with `None` for those that aren't there.
+fn fields(row: &Row) -> [Option<String>; 8] {
+    let (status, answer, error) = match &row.answer {
+        Ok(answer) => ("ok", Some(answer.to_string()), None),
+        Err(_) if row.unsolved() => ("unsolved", None, None),
+        Err(e) => ("error", None, Some(format!("{e:#}"))),
+    };
+
+    [
+        Some(row.day.to_string()),
+        Some(row.part.to_string()),
+        Some(status.to_owned()),
+        answer,
+        Some(row.duration.as_nanos().to_string()),
+        Some(row.input.to_string()),
+        error,
+        Some(row.cached.to_string()),
+    ]
+}
+
+/// `s` as a JSON string, with quotes.
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => json += "\\\"",
+            '\\' => json += "\\\\",
+            '\n' => json += "\\n",
+            '\r' => json += "\\r",
+            '\t' => json += "\\t",
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+/// `s` as a CSV field, quoted if it has to be.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+/// `s` as the text of a Markdown table cell: on one line, with its pipes escaped.
+fn markdown_cell(s: &str) -> String {
+    s.lines().next().unwrap_or("").replace('|', "\\|")
+}
+
+fn write_json<W>(mut w: W, rows: &[Row]) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(w, "[")?;
+
+    for (idx, row) in rows.iter().enumerate() {
+        let comma = if idx + 1 < rows.len() { "," } else { "" };
+
+        let fields = FIELDS.iter().zip(fields(row)).map(|(name, value)| {
+            let value = match (*name, value) {
+                (_, None) => "null".to_owned(),
+                ("day" | "part" | "duration_ns" | "cached", Some(n)) => n,
+                (_, Some(s)) => json_string(&s),
+            };
+
+            format!("\"{name}\": {value}")
+        });
+
+        writeln!(w, "  {{{}}}{comma}", fields.collect::<Vec<_>>().join(", "))?;
+    }
+
+    writeln!(w, "]")
+}
+
+fn write_csv<W>(mut w: W, rows: &[Row]) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(w, "{}", FIELDS.join(","))?;
+
+    for row in rows {
+        let fields = fields(row).map(|value| csv_field(value.as_deref().unwrap_or("")));
+        writeln!(w, "{}", fields.join(","))?;
+    }
+
+    Ok(())
+}
+
+fn write_markdown<W>(mut w: W, rows: &[Row]) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(
+        w,
+        "| day | part | status | answer | time | input | error | cached |"
+    )?;
+    writeln!(
+        w,
+        "| --: | ---: | ------ | ------ | ---: | ----- | ----- | ------ |"
+    )?;
+
+    for row in rows {
+        let [day, part, status, answer, _, input, error, cached] =
+            fields(row).map(|value| markdown_cell(value.as_deref().unwrap_or("")));
+        let time = format!("{:.2?}", row.duration);
+
+        writeln!(
+            w,
+            "| {day} | {part} | {status} | {answer} | {time} | {input} | {error} | {cached} |"
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Write a report of `rows` to `w` in `format`.
+pub fn write<W>(w: W, rows: &[Row], format: ReportFormat) -> io::Result<()>
+where
+    W: Write,
+{
+    match format {
+        ReportFormat::Json => write_json(w, rows),
+        ReportFormat::Csv => write_csv(w, rows),
+        ReportFormat::Markdown => write_markdown(w, rows),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use aoc_2023::error::Unsolved;
+    use aoc_2023::input::Source;
+    use aoc_2023::Part;
+    use std::time::Duration;
+
+    fn rows() -> Vec<Row> {
+        vec![
+            Row {
+                day: 7,
+                part: Part::One,
+                answer: Ok(6440u64.into()),
+                input: Source::from("inputs/day07/input"),
+                duration: Duration::from_micros(1500),
+                cached: false,
+            },
+            Row {
+                day: 7,
+                part: Part::Two,
+                answer: Err(anyhow!("line 3: expected \"five\" cards,\n  | 32T3K")),
+                input: Source::Stdin,
+                duration: Duration::ZERO,
+                cached: false,
+            },
+            Row {
+                day: 8,
+                part: Part::One,
+                answer: Ok("ZZZ".parse().unwrap()),
+                input: Source::from("inputs/day08/input"),
+                duration: Duration::from_nanos(2500),
+                cached: true,
+            },
+            Row {
+                day: 12,
+                part: Part::Two,
+                answer: Err(Unsolved.into()),
+                input: Source::from("inputs/day12/input"),
+                duration: Duration::from_nanos(300),
+                cached: false,
+            },
+        ]
+    }
+
+    fn report(format: ReportFormat) -> String {
+        let mut out = Vec::new();
+        write(&mut out, &rows(), format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            report(ReportFormat::Json),
+            r#"[
+  {"day": 7, "part": 1, "status": "ok", "answer": "6440", "duration_ns": 1500000, "input": "inputs/day07/input", "error": null, "cached": false},
+  {"day": 7, "part": 2, "status": "error", "answer": null, "duration_ns": 0, "input": "-", "error": "line 3: expected \"five\" cards,\n  | 32T3K", "cached": false},
+  {"day": 8, "part": 1, "status": "ok", "answer": "ZZZ", "duration_ns": 2500, "input": "inputs/day08/input", "error": null, "cached": true},
+  {"day": 12, "part": 2, "status": "unsolved", "answer": null, "duration_ns": 300, "input": "inputs/day12/input", "error": null, "cached": false}
+]
+"#
+        );
+
+        assert_eq!(json_string("a\tb\u{1}"), r#""a\tb\u0001""#);
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(
+            report(ReportFormat::Csv),
+            "\
+day,part,status,answer,duration_ns,input,error,cached
+7,1,ok,6440,1500000,inputs/day07/input,,false
+7,2,error,,0,-,\"line 3: expected \"\"five\"\" cards,\n  | 32T3K\",false
+8,1,ok,ZZZ,2500,inputs/day08/input,,true
+12,2,unsolved,,300,inputs/day12/input,,false
+"
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        assert_eq!(
+            report(ReportFormat::Markdown),
+            "\
+| day | part | status | answer | time | input | error | cached |
+| --: | ---: | ------ | ------ | ---: | ----- | ----- | ------ |
+| 7 | 1 | ok | 6440 | 1.50ms | inputs/day07/input |  | false |
+| 7 | 2 | error |  | 0.00ns | - | line 3: expected \"five\" cards, | false |
+| 8 | 1 | ok | ZZZ | 2.50µs | inputs/day08/input |  | true |
+| 12 | 2 | unsolved |  | 300.00ns | inputs/day12/input |  | false |
+"
+        );
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!("csv".parse::<ReportFormat>().unwrap(), ReportFormat::Csv);
+        assert!("xml".parse::<ReportFormat>().is_err());
+    }
+}
@@ -0,0 +1,491 @@
+use crate::args::{Selection, Target};
+use anyhow::{anyhow, Context, Result};
+use aoc_2023::answers::Answer;
+use aoc_2023::bench::Stage;
+use aoc_2023::cache::{self, Cache, Key};
+use aoc_2023::error::Unsolved;
+use aoc_2023::input::Source;
+use aoc_2023::memory::{self, AllocStats, Bytes};
+use aoc_2023::render::{self, Canvas, Format};
+use aoc_2023::{pool, Day, Part, Puzzle, DAYS};
+use std::any::Any;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// The answer to one part of one day.
+pub struct Row {
+    pub day: u8,
+    pub part: Part,
+    pub answer: Result<Answer>,
+
+    /// Where the input was read from.
+    pub input: Source,
+
+    /// How long solving the part took, not counting reading and parsing the input.
+    pub duration: Duration,
+
+    /// Whether the answer came from the [`Cache`], rather than solving the part.
+    pub cached: bool,
+}
+
+impl Row {
+    /// Whether the part isn't solved yet, see [`Unsolved`].
+    pub fn unsolved(&self) -> bool {
+        self.answer.as_ref().is_err_and(Unsolved::is)
+    }
+
+    /// Whether the part has no answer, for any reason but not being solved yet.
+    pub fn failed(&self) -> bool {
+        self.answer.is_err() && !self.unsolved()
+    }
+}
+
+pub fn days(selection: Selection) -> Result<Vec<&'static Day>> {
+    match selection {
+        Selection::All => Ok(DAYS.iter().collect()),
+        Selection::Day(n) => {
+            let day = aoc_2023::day(n).with_context(|| format!("no solution for day {n}"))?;
+            Ok(vec![day])
+        }
+    }
+}
+
+/// The input to use for `day`.
+pub fn source(target: &Target, day: &Day) -> Source {
+    match &target.input {
+        Some(input) => input.clone(),
+        None => Source::for_day(day.number),
+    }
+}
+
+/// The drawing of a day, if it can be drawn.
+pub struct Drawing {
+    pub day: u8,
+    pub canvas: Option<Canvas>,
+}
+
+/// The allocations of a stage of a day, if they are counted, see [`memory`].
+pub struct Usage {
+    pub day: u8,
+    pub stage: Stage,
+    pub stats: AllocStats,
+}
+
+/// Everything [`run`] found out.
+pub struct Report {
+    pub rows: Vec<Row>,
+    pub drawings: Vec<Drawing>,
+
+    /// Allocations of each stage, in the order of the rows. Empty unless they are counted.
+    pub memory: Vec<Usage>,
+}
+
+fn usage(day: &Day, stage: Stage, stats: Option<AllocStats>) -> Option<Usage> {
+    stats.map(|stats| Usage {
+        day: day.number,
+        stage,
+        stats,
+    })
+}
+
+fn parse(day: &Day, input: Result<Vec<u8>>, memory: &mut Vec<Usage>) -> Result<Box<dyn Puzzle>> {
+    let input = input?;
+    let (puzzle, stats) = memory::measure(|| day.parse(input.as_slice()));
+
+    memory.extend(usage(day, Stage::Parse, stats));
+    puzzle
+}
+
+/// Read and parse the input of `day` once, and solve each of `parts` from it.
+pub fn solve(day: &Day, source: &Source, parts: &[Part]) -> Vec<Row> {
+    let mut memory = Vec::new();
+    let puzzle = parse(day, source.read(), &mut memory);
+
+    answer(day, source, &puzzle, parts, &mut memory)
+}
+
+fn answer(
+    day: &Day,
+    source: &Source,
+    puzzle: &Result<Box<dyn Puzzle>>,
+    parts: &[Part],
+    memory: &mut Vec<Usage>,
+) -> Vec<Row> {
+    parts
+        .iter()
+        .map(|&part| {
+            let start = Instant::now();
+
+            let (answer, duration) = match puzzle {
+                Ok(puzzle) => {
+                    let (answer, stats) = memory::measure(|| solve_part(&**puzzle, part));
+                    memory.extend(usage(day, Stage::Part(part), stats));
+
+                    (answer, start.elapsed())
+                }
+                Err(e) => (Err(anyhow!("{e:#}")), Duration::ZERO),
+            };
+
+            Row {
+                day: day.number,
+                part,
+                answer,
+                input: source.clone(),
+                duration,
+                cached: false,
+            }
+        })
+        .collect()
+}
+
+/// The error of a part that panicked with `payload`.
+fn panic_error(payload: &(dyn Any + Send)) -> anyhow::Error {
+    anyhow!("panicked: {}", pool::panic_message(payload))
+}
+
+/// Solve `part` of `puzzle`. A panic only fails this part, not the other one.
+fn solve_part(puzzle: &dyn Puzzle, part: Part) -> Result<Answer> {
+    catch_unwind(AssertUnwindSafe(|| puzzle.solve(part)))
+        .unwrap_or_else(|payload| Err(panic_error(&*payload)))
+}
+
+/// Rows for `parts` of `day`, which all fail because solving the day panicked with `payload`.
+pub fn panicked(
+    day: &Day,
+    source: &Source,
+    parts: &[Part],
+    payload: &(dyn Any + Send),
+) -> Vec<Row> {
+    parts
+        .iter()
+        .map(|&part| Row {
+            day: day.number,
+            part,
+            answer: Err(panic_error(payload)),
+            input: source.clone(),
+            duration: Duration::ZERO,
+            cached: false,
+        })
+        .collect()
+}
+
+/// Solve `parts` of `day` while reading its input line by line, see [`Day::stream`].
+///
+/// Both parts are solved at once, so they can't be timed on their own. The first of `parts` gets
+/// the time and allocations of the whole day, including reading the input.
+fn stream(day: &Day, source: &Source, parts: &[Part], memory: &mut Vec<Usage>) -> Vec<Row> {
+    let start = Instant::now();
+
+    let (answers, stats) = memory::measure(|| match source.open() {
+        Ok(reader) => day
+            .stream(reader)
+            .expect("only days that can stream are streamed"),
+        Err(e) => [Err(anyhow!("{e:#}")), Err(e)],
+    });
+
+    let duration = start.elapsed();
+    let mut answers = answers.map(Some);
+
+    if let Some(&first) = parts.first() {
+        memory.extend(usage(day, Stage::Part(first), stats));
+    }
+
+    parts
+        .iter()
+        .enumerate()
+        .map(|(idx, &part)| {
+            let answer = match part {
+                Part::One => answers[0].take(),
+                Part::Two => answers[1].take(),
+            };
+
+            Row {
+                day: day.number,
+                part,
+                answer: answer.expect("each part is solved once"),
+                input: source.clone(),
+                duration: if idx == 0 { duration } else { Duration::ZERO },
+                cached: false,
+            }
+        })
+        .collect()
+}
+
+/// Rows for the answers to `parts` of `day` that are in `cache`, for an input with hash `input`.
+fn cached(cache: &Cache, day: &Day, source: &Source, input: u64, parts: &[Part]) -> Vec<Row> {
+    parts
+        .iter()
+        .filter_map(|&part| {
+            let start = Instant::now();
+            let answer = cache.get(&Key::new(day, part, input))?;
+
+            Some(Row {
+                day: day.number,
+                part,
+                answer: Ok(answer),
+                input: source.clone(),
+                duration: start.elapsed(),
+                cached: true,
+            })
+        })
+        .collect()
+}
+
+/// Cache the answers in `rows`, for an input with hash `input`. Failing to is only a warning.
+fn store(cache: &Cache, day: &Day, input: u64, rows: &[Row]) {
+    for row in rows {
+        if let Ok(answer) = &row.answer {
+            if let Err(e) = cache.put(&Key::new(day, row.part, input), answer) {
+                eprintln!("warning: {e:#}");
+            }
+        }
+    }
+}
+
+/// Solve all targeted days, on up to `jobs` threads, and draw them too if `draw` is set.
+///
+/// With `stream`, days that can be solved line by line are, and the others are solved as usual.
+///
+/// With a `cache`, answers are taken from it where they can be, and the others are cached once
+/// they are found. A day is only parsed if it has parts to solve, or is to be drawn. Days that are
+/// streamed aren't cached, as their inputs may be too big to read twice.
+///
+/// A part that panics fails, but not the other part. A day that panics while reading, parsing or
+/// streaming its input fails all of its parts. Neither stops the other days. The rows are ordered
+/// by day and part, however long each day takes. There is a drawing for each day whose input was
+/// parsed.
+pub fn run(
+    target: &Target,
+    jobs: usize,
+    draw: bool,
+    streaming: bool,
+    cache: Option<&Cache>,
+) -> Result<Report> {
+    let parts = target.parts();
+    let days = days(target.selection)?;
+
+    let results = pool::map(days.clone(), jobs, |day| {
+        let source = source(target, day);
+        let mut memory = Vec::new();
+
+        if streaming && day.can_stream() {
+            let rows = stream(day, &source, &parts, &mut memory);
+            return (rows, None, memory);
+        }
+
+        let input = source.read();
+        let hash = input.as_ref().ok().map(|input| cache::fnv1a(input));
+
+        let mut rows = match (cache, hash) {
+            (Some(cache), Some(hash)) => cached(cache, day, &source, hash, &parts),
+            _ => Vec::new(),
+        };
+
+        let missing = parts
+            .iter()
+            .copied()
+            .filter(|&part| rows.iter().all(|row| row.part != part))
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() && !draw {
+            return (rows, None, memory);
+        }
+
+        let puzzle = parse(day, input, &mut memory);
+        let solved = answer(day, &source, &puzzle, &missing, &mut memory);
+
+        if let (Some(cache), Some(hash)) = (cache, hash) {
+            store(cache, day, hash, &solved);
+        }
+
+        rows.extend(solved);
+        rows.sort_by_key(|row| row.part);
+
+        let drawing = match puzzle {
+            Ok(puzzle) if draw => Some(Drawing {
+                day: day.number,
+                canvas: puzzle.render(),
+            }),
+            _ => None,
+        };
+
+        (rows, drawing, memory)
+    });
+
+    let mut drawings = Vec::new();
+    let mut memory = Vec::new();
+
+    let rows = days
+        .into_iter()
+        .zip(results)
+        .flat_map(|(day, result)| match result {
+            Ok((rows, drawing, usage)) => {
+                drawings.extend(drawing);
+                memory.extend(usage);
+                rows
+            }
+            Err(payload) => panicked(day, &source(target, day), &parts, &*payload),
+        })
+        .collect();
+
+    Ok(Report {
+        rows,
+        drawings,
+        memory,
+    })
+}
+
+/// Show `drawings` in `format`: on stdout for ANSI text, or in `day<n>` files otherwise.
+///
+/// Days that can't be drawn are only mentioned if they were asked for by themselves.
+pub fn show(drawings: &[Drawing], format: Format, selection: Selection) -> Result<()> {
+    for drawing in drawings {
+        let Some(canvas) = &drawing.canvas else {
+            if selection != Selection::All {
+                eprintln!("day {} can't be drawn", drawing.day);
+            }
+
+            continue;
+        };
+
+        if format == Format::Ansi {
+            println!();
+            println!("day {}:", drawing.day);
+            render::write(io::stdout().lock(), canvas, format)?;
+            continue;
+        }
+
+        let path = PathBuf::from(format!("day{:02}.{}", drawing.day, format.extension()));
+        let context = || format!("could not write {}", path.display());
+
+        let mut file = BufWriter::new(File::create(&path).with_context(context)?);
+        render::write(&mut file, canvas, format).with_context(context)?;
+        file.flush().with_context(context)?;
+
+        println!("drew day {} in {}", drawing.day, path.display());
+    }
+
+    Ok(())
+}
+
+pub fn print_table(rows: &[Row]) {
+    println!("{:>3}  {:>4}  {:>10}  answer", "day", "part", "time");
+
+    for row in rows {
+        let time = if row.cached {
+            "cached".to_owned()
+        } else {
+            format!("{:.2?}", row.duration)
+        };
+
+        match &row.answer {
+            Ok(answer) => println!("{:>3}  {:>4}  {time:>10}  {answer}", row.day, row.part),
+            Err(_) if row.unsolved() => {
+                println!("{:>3}  {:>4}  {time:>10}  unsolved", row.day, row.part)
+            }
+            Err(e) => println!("{:>3}  {:>4}  {time:>10}  error: {e:#}", row.day, row.part),
+        }
+    }
+}
+
+/// Print the allocations of each stage, if there are any.
+pub fn print_memory(memory: &[Usage]) {
+    if memory.is_empty() {
+        return;
+    }
+
+    println!();
+    println!(
+        "{:>3}  {:<6}  {:>9}  {:>10}  {:>10}",
+        "day", "stage", "allocs", "allocated", "peak"
+    );
+
+    for usage in memory {
+        let s = &usage.stats;
+        println!(
+            "{:>3}  {:<6}  {:>9}  {:>10}  {:>10}",
+            usage.day,
+            usage.stage,
+            s.allocations,
+            Bytes(s.bytes),
+            Bytes(s.peak)
+        );
+    }
+}
+
+/// Print how many parts were answered, how long it took, and which parts failed or are unsolved.
+pub fn print_summary(rows: &[Row], elapsed: Duration) {
+    let failures = rows.iter().filter(|row| row.failed()).collect::<Vec<_>>();
+    let unsolved = rows.iter().filter(|row| row.unsolved()).collect::<Vec<_>>();
+    let solving = rows.iter().map(|row| row.duration).sum::<Duration>();
+
+    println!();
+    println!(
+        "{} of {} parts answered in {elapsed:.2?} ({solving:.2?} spent solving)",
+        rows.len() - failures.len() - unsolved.len(),
+        rows.len(),
+    );
+
+    if !failures.is_empty() {
+        println!();
+        println!("failures:");
+
+        for row in failures {
+            if let Err(e) = &row.answer {
+                println!("    day {} part {}: {e:#}", row.day, row.part);
+            }
+        }
+    }
+
+    if !unsolved.is_empty() {
+        println!();
+        println!("unsolved:");
+
+        for row in unsolved {
+            println!("    day {} part {}", row.day, row.part);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt;
+
+    /// A puzzle whose first part panics.
+    struct Panicky;
+
+    impl Puzzle for Panicky {
+        fn solve(&self, part: Part) -> Result<Answer> {
+            match part {
+                Part::One => panic!("no part one"),
+                Part::Two => Ok(2u8.into()),
+            }
+        }
+
+        fn render(&self) -> Option<Canvas> {
+            None
+        }
+    }
+
+    impl fmt::Display for Panicky {
+        fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn panics_fail_one_part() {
+        let day = aoc_2023::day(1).unwrap();
+        let puzzle: Result<Box<dyn Puzzle>> = Ok(Box::new(Panicky));
+
+        let rows = answer(day, &Source::Stdin, &puzzle, &Part::BOTH, &mut Vec::new());
+
+        let e = rows[0].answer.as_ref().unwrap_err();
+        assert_eq!(e.to_string(), "panicked: no part one");
+        assert_eq!(rows[1].answer.as_ref().unwrap(), &Answer::from(2u8));
+    }
+}
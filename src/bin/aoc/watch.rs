@@ -0,0 +1,316 @@
+use crate::args::Target;
+use crate::run::{self, Row};
+use anyhow::{Context, Result};
+use aoc_2023::answers::{Answers, Status};
+use aoc_2023::examples;
+use aoc_2023::input::{self, Source};
+use aoc_2023::{Day, Part};
+use std::collections::BTreeMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+use std::{env, fs, thread};
+
+/// How often to look for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Environment variable that passes the answers of the last run on to a rebuilt `aoc`.
+const PREVIOUS_VAR: &str = "AOC_WATCH_PREVIOUS";
+
+/// What a run solved, e.g. `ghosts part 2`, and the answers or errors it got.
+type Answered = BTreeMap<String, String>;
+
+/// When a file was last changed, and its size, to notice changes within the same second.
+type Stamp = (SystemTime, u64);
+
+/// One answer of a run.
+struct Line {
+    name: String,
+    answer: String,
+
+    /// How the answer compares to the recorded one, for examples.
+    status: Option<Status>,
+}
+
+fn answer_text(row: &Row) -> String {
+    match &row.answer {
+        Ok(answer) => answer.to_string(),
+        Err(_) if row.unsolved() => "unsolved".to_owned(),
+        Err(e) => format!("error: {e:#}").replace('\n', " "),
+    }
+}
+
+/// Like [`run::solve`], but a panic fails the parts instead of stopping the watch.
+fn solve_parts(day: &Day, source: &Source, parts: &[Part]) -> Vec<Row> {
+    catch_unwind(AssertUnwindSafe(|| run::solve(day, source, parts)))
+        .unwrap_or_else(|payload| run::panicked(day, source, parts, &*payload))
+}
+
+/// Solve the targeted parts of `day` for its input and for each of its examples.
+fn solve(day: &Day, target: &Target) -> Result<Vec<Line>> {
+    let mut lines = Vec::new();
+
+    for row in solve_parts(day, &run::source(target, day), &target.parts()) {
+        lines.push(Line {
+            name: format!("input part {}", row.part),
+            answer: answer_text(&row),
+            status: None,
+        });
+    }
+
+    let examples = examples::discover(input::inputs_dir())?;
+
+    for example in examples.iter().filter(|e| e.day == day.number) {
+        let parts = example
+            .parts()
+            .into_iter()
+            .filter(|&part| target.part.is_none_or(|p| p == part))
+            .collect::<Vec<_>>();
+
+        for row in solve_parts(day, &Source::File(example.path.clone()), &parts) {
+            let status = match &row.answer {
+                Ok(answer) => Some(example.answers.check(row.part, answer)),
+                Err(_) => None,
+            };
+
+            lines.push(Line {
+                name: format!("{} part {}", example.name, row.part),
+                answer: answer_text(&row),
+                status,
+            });
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Print `lines`, with how they changed since the `previous` run.
+fn print(lines: &[Line], previous: &Answered) {
+    let width = lines.iter().map(|line| line.name.len()).max().unwrap_or(0);
+
+    for line in lines {
+        let status = match &line.status {
+            Some(Status::Pass) => "  pass".to_owned(),
+            Some(Status::Fail { expected }) => format!("  FAIL, expected {expected}"),
+            Some(Status::Missing) | None => String::new(),
+        };
+
+        let change = match previous.get(&line.name) {
+            Some(before) if *before != line.answer => format!("  (was {before})"),
+            None if !previous.is_empty() => "  (new)".to_owned(),
+            _ => String::new(),
+        };
+
+        println!("{:<width$}  {}{status}{change}", line.name, line.answer);
+    }
+}
+
+/// The input, its answers and the examples of `day`.
+fn watched_files(day: &Day, target: &Target) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    if let Source::File(input) = run::source(target, day) {
+        paths.push(Answers::path_for(&input));
+        paths.push(input);
+    }
+
+    let dir = examples::dir_for_day_in(input::inputs_dir(), day.number);
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        paths.extend(entries.flatten().map(|entry| entry.path()));
+    }
+
+    paths
+}
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Stamps of the files among `paths` that exist.
+fn stamps(paths: &[PathBuf]) -> BTreeMap<PathBuf, Stamp> {
+    paths
+        .iter()
+        .filter_map(|path| Some((path.clone(), stamp(path)?)))
+        .collect()
+}
+
+/// A file that was added, removed or changed between `before` and `after`, if any was.
+fn changed<'a>(
+    before: &'a BTreeMap<PathBuf, Stamp>,
+    after: &'a BTreeMap<PathBuf, Stamp>,
+) -> Option<&'a Path> {
+    let removed = before.keys().find(|path| !after.contains_key(*path));
+    let changed = after.iter().find(|&(path, s)| before.get(path) != Some(s));
+
+    removed
+        .or(changed.map(|(path, _)| path))
+        .map(PathBuf::as_path)
+}
+
+/// The source of `day` in this crate, if `aoc` is run from where it was built.
+fn source_file(day: &Day) -> Option<PathBuf> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join(format!("day{:02}.rs", day.number));
+
+    path.exists().then_some(path)
+}
+
+fn encode(answered: &Answered) -> String {
+    answered
+        .iter()
+        .map(|(name, answer)| format!("{name}\t{answer}\n"))
+        .collect()
+}
+
+fn decode(s: &str) -> Answered {
+    s.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(name, answer)| (name.to_owned(), answer.to_owned()))
+        .collect()
+}
+
+/// Build `aoc` again, with the same profile as this one. Returns whether that worked.
+fn rebuild() -> Result<bool> {
+    let mut cargo = Command::new(env::var_os("CARGO").unwrap_or("cargo".into()));
+    cargo
+        .args(["build", "--bin", "aoc"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"));
+
+    if !cfg!(debug_assertions) {
+        cargo.arg("--release");
+    }
+
+    let status = cargo.status().context("could not run cargo")?;
+    Ok(status.success())
+}
+
+/// Replace this process by the rebuilt `aoc` at `exe`, with the same arguments.
+fn restart(exe: &Path, answered: &Answered) -> Result<()> {
+    let mut aoc = Command::new(exe);
+    aoc.args(env::args_os().skip(1))
+        .env(PREVIOUS_VAR, encode(answered));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+
+        // only returns if it failed
+        Err(aoc.exec()).context("could not restart aoc")
+    }
+
+    #[cfg(not(unix))]
+    {
+        let status = aoc.status().context("could not restart aoc")?;
+        std::process::exit(status.code().unwrap_or(1));
+    }
+}
+
+/// Solve `target`, which is a single day, and solve it again whenever its input, examples or
+/// source change. A change to the source rebuilds `aoc` and restarts it. Runs until stopped.
+pub fn watch(target: &Target) -> Result<()> {
+    let day = run::days(target.selection)?[0];
+    let source = source_file(day);
+
+    // once cargo replaces the executable, the running one can no longer be found by its path
+    let exe = env::current_exe().context("could not find the aoc executable")?;
+
+    let mut previous = env::var(PREVIOUS_VAR)
+        .map(|s| decode(&s))
+        .unwrap_or_default();
+
+    let mut files = stamps(&watched_files(day, target));
+    let mut code = source.as_deref().and_then(stamp);
+
+    println!("watching day {}, stop with Ctrl-C", day.number);
+
+    loop {
+        println!();
+
+        let lines = solve(day, target)?;
+        print(&lines, &previous);
+
+        previous = lines
+            .into_iter()
+            .map(|line| (line.name, line.answer))
+            .collect();
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            if let Some(path) = &source {
+                let now = stamp(path);
+
+                if now != code {
+                    code = now;
+                    println!();
+                    println!("{} changed, rebuilding", path.display());
+
+                    if rebuild()? {
+                        restart(&exe, &previous)?;
+                    }
+
+                    // keep watching until the build is fixed
+                    continue;
+                }
+            }
+
+            let now = stamps(&watched_files(day, target));
+
+            if let Some(path) = changed(&files, &now) {
+                println!();
+                println!("{} changed", path.display());
+
+                files = now;
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previous_answers() {
+        let mut answered = Answered::new();
+        answered.insert("input part 1".to_owned(), "42".to_owned());
+        answered.insert("ghosts part 2".to_owned(), "error: no ghosts".to_owned());
+
+        assert_eq!(decode(&encode(&answered)), answered);
+    }
+
+    #[test]
+    fn changes() {
+        let t = SystemTime::UNIX_EPOCH;
+        let files = |stamps: &[(&str, u64)]| {
+            stamps
+                .iter()
+                .map(|&(path, len)| (PathBuf::from(path), (t, len)))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let before = files(&[("input", 10), ("example", 5)]);
+
+        assert_eq!(changed(&before, &before), None);
+        assert_eq!(
+            changed(&before, &files(&[("input", 11), ("example", 5)])),
+            Some(Path::new("input"))
+        );
+        assert_eq!(
+            changed(&before, &files(&[("input", 10)])),
+            Some(Path::new("example"))
+        );
+        assert_eq!(
+            changed(
+                &before,
+                &files(&[("input", 10), ("example", 5), ("new", 1)])
+            ),
+            Some(Path::new("new"))
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Answers cached on disk, so that running a day again on the same input is instant.
+//!
+//! An answer is cached for a [`Key`]: the day and part, a hash of the input, and the version of the
+//! solver, see [`Solution::VERSION`](crate::Solution::VERSION). It is stored in
+//! `<dir>/day<n>/part<p>/<hash>` with the version on the first line, so that an answer of another
+//! version is a miss, and is overwritten by the next answer for the same input.
+//!
+//! The cache is in `.aoc-cache` in the current directory, or in the directory given by the
+//! `AOC_CACHE` environment variable.
+
+use crate::answers::Answer;
+use crate::checked::WIDE;
+use crate::{Day, Part};
+use anyhow::{Context, Result};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that overrides [`DEFAULT_CACHE_DIR`].
+pub const CACHE_DIR_VAR: &str = "AOC_CACHE";
+
+pub const DEFAULT_CACHE_DIR: &str = ".aoc-cache";
+
+/// The directory of the cache.
+pub fn cache_dir() -> PathBuf {
+    match std::env::var_os(CACHE_DIR_VAR) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(DEFAULT_CACHE_DIR),
+    }
+}
+
+/// The 64-bit FNV-1a hash of `bytes`.
+///
+/// Not a cryptographic hash, but inputs aren't picked to collide, and it is simple and fast.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// What an answer is cached for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    pub day: u8,
+    pub part: Part,
+    /// The [`fnv1a`] hash of the input.
+    pub input: u64,
+    pub version: String,
+}
+
+impl Key {
+    /// The key of `part` of `day` for an input with hash `input`.
+    ///
+    /// Besides the version of the day's solver, the version includes that of this crate, and
+    /// whether answers are computed in 128 bits, as that decides which answers overflow.
+    pub fn new(day: &Day, part: Part, input: u64) -> Self {
+        let wide = if WIDE { "+wide" } else { "" };
+
+        Self {
+            day: day.number,
+            part,
+            input,
+            version: format!("{}/{}{wide}", env!("CARGO_PKG_VERSION"), day.version()),
+        }
+    }
+}
+
+/// A directory of cached answers, see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new<P>(dir: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self { dir: dir.into() }
+    }
+
+    /// The cache in [`cache_dir`].
+    pub fn open() -> Self {
+        Self::new(cache_dir())
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn day_dir(&self, day: u8) -> PathBuf {
+        self.dir.join(format!("day{day:02}"))
+    }
+
+    fn path(&self, key: &Key) -> PathBuf {
+        self.day_dir(key.day)
+            .join(format!("part{}", key.part))
+            .join(format!("{:016x}", key.input))
+    }
+
+    /// The cached answer for `key`, if there is one.
+    ///
+    /// A cache that can't be read has no answers.
+    pub fn get(&self, key: &Key) -> Option<Answer> {
+        let cached = fs::read_to_string(self.path(key)).ok()?;
+        let (version, answer) = cached.split_once('\n')?;
+
+        if version != key.version {
+            return None;
+        }
+
+        let Ok(answer) = answer.strip_suffix('\n').unwrap_or(answer).parse();
+        Some(answer)
+    }
+
+    /// Cache `answer` for `key`.
+    pub fn put(&self, key: &Key, answer: &Answer) -> Result<()> {
+        let path = self.path(key);
+        let context = || format!("could not cache an answer in {}", path.display());
+
+        fs::create_dir_all(path.parent().expect("answers are in a directory"))
+            .with_context(context)?;
+
+        // days run in parallel, but never the same day, so a part only has one writer at a time
+        let partial = path.with_extension("partial");
+        fs::write(&partial, format!("{}\n{answer}\n", key.version)).with_context(context)?;
+        fs::rename(&partial, &path).with_context(context)?;
+
+        Ok(())
+    }
+
+    /// Remove the cached answers of `day`, or of all days. Returns how many there were.
+    ///
+    /// Only the files and directories the cache writes are removed. Anything else in its
+    /// directory, which may not be the cache's alone, is left where it is.
+    pub fn clear(&self, day: Option<u8>) -> Result<usize> {
+        let days = match day {
+            Some(day) => vec![self.day_dir(day)],
+            None => subdirs(&self.dir, "day")
+                .with_context(|| format!("could not read {}", self.dir.display()))?,
+        };
+
+        let mut count = 0;
+
+        for day in days {
+            count +=
+                clear_day(&day).with_context(|| format!("could not clear {}", day.display()))?;
+        }
+
+        Ok(count)
+    }
+}
+
+/// The directories in `dir` named `prefix` and a number, like `day07`, or none if there's no `dir`.
+fn subdirs(dir: &Path, prefix: &str) -> io::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut dirs = Vec::new();
+
+    for entry in entries {
+        let entry = entry?;
+
+        let numbered = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix(prefix))
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+
+        if numbered && entry.file_type()?.is_dir() {
+            dirs.push(entry.path());
+        }
+    }
+
+    Ok(dirs)
+}
+
+/// Whether `name` is that of a file the cache writes: a hash, or an answer being written.
+fn is_answer(name: &str) -> bool {
+    let hash = name.strip_suffix(".partial").unwrap_or(name);
+    hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Remove the answers in `day`, and its directories if that leaves them empty. Returns how many
+/// answers were removed.
+fn clear_day(day: &Path) -> io::Result<usize> {
+    let mut count = 0;
+
+    for part in subdirs(day, "part")? {
+        for entry in fs::read_dir(&part)? {
+            let entry = entry?;
+            let answer = entry.file_name().to_str().is_some_and(is_answer);
+
+            if answer && entry.file_type()?.is_file() {
+                fs::remove_file(entry.path())?;
+                count += 1;
+            }
+        }
+
+        remove_if_empty(&part)?;
+    }
+
+    remove_if_empty(day)?;
+
+    Ok(count)
+}
+
+/// Remove `dir` if it is empty. It is fine if it doesn't exist.
+fn remove_if_empty(dir: &Path) -> io::Result<()> {
+    let mut entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    match entries.next() {
+        None => fs::remove_dir(dir),
+        Some(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes() {
+        // from the reference test vectors
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn caching() {
+        let dir = std::env::temp_dir().join(format!("aoc-cache-{}", std::process::id()));
+        let cache = Cache::new(&dir);
+
+        let day = crate::day(7).unwrap();
+        let key = Key::new(day, Part::One, fnv1a(b"32T3K 765\n"));
+
+        assert_eq!(cache.get(&key), None);
+
+        cache.put(&key, &Answer::from(765u64)).unwrap();
+        assert_eq!(cache.get(&key), Some(Answer::from(765u64)));
+
+        let other_input = Key::new(day, Part::One, fnv1a(b"32T3K 766\n"));
+        assert_eq!(cache.get(&other_input), None);
+
+        let other_part = Key::new(day, Part::Two, key.input);
+        assert_eq!(cache.get(&other_part), None);
+
+        // a new version of the solver doesn't see the old answer, and replaces it
+        let mut newer = key.clone();
+        newer.version += "-newer";
+        assert_eq!(cache.get(&newer), None);
+
+        cache.put(&newer, &Answer::Text("text".to_owned())).unwrap();
+        assert_eq!(cache.get(&newer), Some(Answer::Text("text".to_owned())));
+        assert_eq!(cache.get(&key), None);
+
+        cache.put(&other_part, &Answer::from(0u8)).unwrap();
+        assert_eq!(cache.clear(Some(8)).unwrap(), 0);
+        assert_eq!(cache.clear(Some(7)).unwrap(), 2);
+        assert_eq!(cache.get(&other_part), None);
+
+        cache.put(&key, &Answer::from(765u64)).unwrap();
+        assert_eq!(cache.clear(None).unwrap(), 1);
+        assert_eq!(cache.clear(None).unwrap(), 0);
+
+        // the directory itself is kept
+        fs::remove_dir(&dir).unwrap();
+        assert_eq!(cache.clear(None).unwrap(), 0);
+    }
+
+    #[test]
+    fn clearing_keeps_other_files() {
+        let dir = std::env::temp_dir().join(format!("aoc-cache-clear-{}", std::process::id()));
+        let cache = Cache::new(&dir);
+
+        let day = crate::day(7).unwrap();
+        cache
+            .put(&Key::new(day, Part::One, 1), &Answer::from(1u8))
+            .unwrap();
+        cache
+            .put(&Key::new(day, Part::Two, 1), &Answer::from(2u8))
+            .unwrap();
+
+        // the directory may be shared with something else, or not be a cache at all
+        let foreign = [
+            dir.join("notes.txt"),
+            dir.join("days").join("0000000000000001"),
+            dir.join("day07").join("notes.txt"),
+            dir.join("day07").join("part1").join("notes.txt"),
+        ];
+
+        for path in foreign.iter() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "keep").unwrap();
+        }
+
+        assert_eq!(cache.clear(None).unwrap(), 2);
+
+        for path in foreign.iter() {
+            assert!(path.exists(), "{} was removed", path.display());
+        }
+
+        assert!(!dir.join("day07").join("part2").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+//! Arithmetic that fails with an [`OverflowError`] rather than wrapping around or panicking.
+//!
+//! Solvers compute their answers in [`Int`], or [`SignedInt`] if they can be negative. Both are 64
+//! bits wide, or 128 bits with the `wide` feature, for generated stress inputs whose answers don't
+//! fit in 64 bits:
+//!
+//! ```text
+//! cargo run --release --features wide -- run 7 --input big
+//! ```
+
+use crate::error::{Op, OverflowError};
+use num::{CheckedAdd, CheckedMul, CheckedSub, One, Zero};
+use std::any::type_name;
+
+/// Whether answers are computed in 128 bits, i.e. if the `wide` feature is enabled.
+pub const WIDE: bool = cfg!(feature = "wide");
+
+/// The type of answers that can't be negative.
+#[cfg(not(feature = "wide"))]
+pub type Int = u64;
+
+/// The type of answers that can't be negative.
+#[cfg(feature = "wide")]
+pub type Int = u128;
+
+/// The type of answers that can be negative.
+#[cfg(not(feature = "wide"))]
+pub type SignedInt = i64;
+
+/// The type of answers that can be negative.
+#[cfg(feature = "wide")]
+pub type SignedInt = i128;
+
+pub fn add<T>(a: T, b: T) -> Result<T, OverflowError>
+where
+    T: CheckedAdd,
+{
+    a.checked_add(&b)
+        .ok_or_else(|| OverflowError::new(Op::Add, type_name::<T>()))
+}
+
+pub fn sub<T>(a: T, b: T) -> Result<T, OverflowError>
+where
+    T: CheckedSub,
+{
+    a.checked_sub(&b)
+        .ok_or_else(|| OverflowError::new(Op::Sub, type_name::<T>()))
+}
+
+pub fn mul<T>(a: T, b: T) -> Result<T, OverflowError>
+where
+    T: CheckedMul,
+{
+    a.checked_mul(&b)
+        .ok_or_else(|| OverflowError::new(Op::Mul, type_name::<T>()))
+}
+
+/// `base` to the power of `exp`.
+pub fn pow<T>(base: T, exp: u32) -> Result<T, OverflowError>
+where
+    T: CheckedMul + One + Clone,
+{
+    num::checked_pow(base, exp as usize)
+        .ok_or_else(|| OverflowError::new(Op::Mul, type_name::<T>()))
+}
+
+/// Convert `n` to a type it may not fit in, like a `u64` to a `u32`.
+pub fn convert<T, U>(n: T) -> Result<U, OverflowError>
+where
+    U: TryFrom<T>,
+{
+    U::try_from(n).map_err(|_| OverflowError::new(Op::Convert, type_name::<U>()))
+}
+
+/// The sum of `values`, each converted to `T` first.
+pub fn sum<T, V, I>(values: I) -> Result<T, OverflowError>
+where
+    I: IntoIterator<Item = V>,
+    V: Into<T>,
+    T: CheckedAdd + Zero,
+{
+    values
+        .into_iter()
+        .try_fold(T::zero(), |total, value| add(total, value.into()))
+}
+
+/// The sum of `values`, or the first error among them.
+pub fn try_sum<T, V, E, I>(values: I) -> Result<T, E>
+where
+    I: IntoIterator<Item = Result<V, E>>,
+    V: Into<T>,
+    T: CheckedAdd + Zero,
+    E: From<OverflowError>,
+{
+    values
+        .into_iter()
+        .try_fold(T::zero(), |total, value| Ok(add(total, value?.into())?))
+}
+
+/// The product of `values`, each converted to `T` first.
+pub fn product<T, V, I>(values: I) -> Result<T, OverflowError>
+where
+    I: IntoIterator<Item = V>,
+    V: Into<T>,
+    T: CheckedMul + One,
+{
+    values
+        .into_iter()
+        .try_fold(T::one(), |total, value| mul(total, value.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflows() {
+        assert_eq!(add(1u8, 2), Ok(3));
+        assert_eq!(sub(1i32, 2), Ok(-1));
+        assert_eq!(mul(16u32, 16), Ok(256));
+        assert_eq!(pow(2u64, 63), Ok(1 << 63));
+        assert!(pow(2u64, 64).is_err());
+
+        let e = add(200u8, 100).unwrap_err();
+        assert_eq!(e, OverflowError::new(Op::Add, "u8"));
+        assert!(e.to_string().starts_with("u8 addition overflowed"));
+
+        assert_eq!(sub(0u64, 1).unwrap_err().op(), Op::Sub);
+        assert_eq!(mul(Int::MAX, 2).unwrap_err().ty(), type_name::<Int>());
+
+        assert_eq!(convert::<u64, u32>(7), Ok(7));
+        assert_eq!(
+            convert::<i64, u32>(-1)
+                .unwrap_err()
+                .to_string()
+                .split(',')
+                .next(),
+            Some("number does not fit in u32")
+        );
+    }
+
+    #[test]
+    fn sums_and_products() {
+        assert_eq!(sum::<Int, _, _>([1u32, 2, 3]), Ok(6));
+        assert_eq!(product::<Int, _, _>([2u8, 3, 7]), Ok(42));
+        assert_eq!(product::<Int, u32, _>([]), Ok(1));
+
+        assert!(sum::<u32, _, _>([u32::MAX, 1]).is_err());
+        assert!(product::<Int, _, _>([Int::MAX, 2]).is_err());
+        assert_eq!(sum::<Int, _, _>([u32::MAX, 1]), Ok(1 << 32));
+
+        let values: [anyhow::Result<u8>; 2] = [Ok(255), Ok(1)];
+        assert_eq!(try_sum::<Int, _, _, _>(values).unwrap(), 256);
+
+        let values: [anyhow::Result<u8>; 2] = [Ok(255), Ok(1)];
+        let e = try_sum::<u8, _, _, _>(values).unwrap_err();
+        assert!(e.downcast_ref::<OverflowError>().is_some());
+
+        let values = [Ok(1), Err(anyhow::anyhow!("no"))];
+        let e = try_sum::<Int, u8, _, _>(values).unwrap_err();
+        assert_eq!(e.to_string(), "no");
+    }
+}
@@ -1,7 +1,15 @@
+use crate::checked::{self, Int};
+use crate::generate::{Knob, Knobs};
+use crate::rng::Rng;
+use crate::stream;
+use crate::{Solution, Streaming};
+use anyhow::{ensure, Context, Result};
+use std::fmt;
+use std::io::BufRead;
 use std::str::Chars;
 
-/// Compute the calibration value from an iterator of numbers (digits).
-pub fn calibration_value<I>(iter: I) -> u32
+/// Compute the calibration value from an iterator of numbers (digits), if there are any.
+pub fn calibration_value<I>(iter: I) -> Option<u32>
 where
     I: Iterator<Item = u32>,
 {
@@ -15,7 +23,7 @@ where
             last.replace(digit);
         }
     }
-    let first = first.unwrap();
+    let first = first?;
 
     let mut value = first * 10;
 
@@ -24,7 +32,7 @@ where
         None => value += first,
     }
 
-    value
+    Some(value)
 }
 
 /// An iterator over a string that yields its digits, spelled out or not.
@@ -76,7 +84,8 @@ impl<'a> Iterator for Digits<'a> {
         loop {
             let c = match self.chars.next() {
                 Some(chr) => chr,
-                None => return Self::str_to_digit(&self.buf),
+                // whatever is left is yielded once, and then the iterator is done
+                None => return Self::str_to_digit(&std::mem::take(&mut self.buf)),
             };
 
             if let Some(digit) = c.to_digit(10) {
@@ -86,19 +95,144 @@ impl<'a> Iterator for Digits<'a> {
 
             self.buf.push(c);
 
-            if let Some(digit) = Self::str_to_digit(&self.buf) {
-                self.buf.clear();
-                self.buf.push(c);
-                return Some(digit);
-            }
+            // trim the buffer until it is a digit, or could still become one
+            while !self.buf.is_empty() {
+                if let Some(digit) = Self::str_to_digit(&self.buf) {
+                    // the last letter may start the next digit, like in "eightwo"
+                    self.buf.clear();
+                    self.buf.push(c);
+                    return Some(digit);
+                }
+
+                if Self::matches_any_prefix(&self.buf) {
+                    break;
+                }
 
-            if !Self::matches_any_prefix(&self.buf) {
                 self.buf.remove(0);
             }
         }
     }
 }
 
+/// The calibration value of line `line_no` for part one, which only counts actual digits.
+fn value_one(line_no: usize, line: &str) -> Result<u32> {
+    calibration_value(line.chars().filter_map(|chr| chr.to_digit(10)))
+        .with_context(|| format!("line {line_no} has no digits"))
+}
+
+/// The calibration value of line `line_no` for part two, which counts spelled out digits too.
+fn value_two(line_no: usize, line: &str) -> Result<u32> {
+    calibration_value(Digits::new(line))
+        .with_context(|| format!("line {line_no} has no digits, spelled out or not"))
+}
+
+/// The calibration document, one calibration value per line.
+#[derive(Debug)]
+pub struct Document {
+    lines: Vec<String>,
+}
+
+impl Solution for Document {
+    type PartOne = Int;
+    type PartTwo = Int;
+
+    fn parse<R>(reader: R) -> Result<Self>
+    where
+        R: BufRead,
+    {
+        let lines = reader.lines().collect::<std::io::Result<_>>()?;
+        Ok(Self { lines })
+    }
+
+    fn part_one(&self) -> Result<Int> {
+        checked::try_sum(
+            self.lines
+                .iter()
+                .enumerate()
+                .map(|(idx, line)| value_one(idx + 1, line)),
+        )
+    }
+
+    fn part_two(&self) -> Result<Int> {
+        checked::try_sum(
+            self.lines
+                .iter()
+                .enumerate()
+                .map(|(idx, line)| value_two(idx + 1, line)),
+        )
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Streaming for Document {
+    fn stream<R>(reader: R) -> (Result<Int>, Result<Int>)
+    where
+        R: BufRead,
+    {
+        // a line can have digits for one part but not the other, so each part fails on its own
+        let totals = stream::fold_lines(reader, (Ok(0), Ok(0)), |(one, two), n, line| {
+            let one: Result<Int> =
+                one.and_then(|total| Ok(checked::add(total, value_one(n, line)?.into())?));
+            let two: Result<Int> =
+                two.and_then(|total| Ok(checked::add(total, value_two(n, line)?.into())?));
+            Ok((one, two))
+        });
+
+        let (one, two) = stream::split(totals);
+        (one.and_then(|one| one), two.and_then(|two| two))
+    }
+}
+
+pub const KNOBS: &[Knob] = &[
+    Knob::new("lines", 1000, "number of lines"),
+    Knob::new("length", 40, "maximum length of a line, roughly"),
+];
+
+fn random_digit(rng: &mut Rng) -> char {
+    char::from(b'1' + rng.below(9) as u8)
+}
+
+/// Generate a calibration document, with spelled out digits among the letters for part two.
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> Result<String> {
+    let length = knobs.get("length");
+    ensure!(length > 0, "lines need at least one character");
+
+    let mut document = String::new();
+
+    for _ in 0..knobs.get("lines") {
+        let target = rng.range_inclusive(1..=length) as usize;
+        let mut line = String::new();
+
+        while line.len() < target {
+            match rng.below(10) {
+                0 => line.push(random_digit(rng)),
+                1 => line += Digits::SPELLED_DIGITS[rng.range(1..10) as usize],
+                _ => line.push(char::from(b'a' + rng.below(26) as u8)),
+            }
+        }
+
+        // part one needs a digit on every line
+        if !line.bytes().any(|b| b.is_ascii_digit()) {
+            let at = rng.below(line.len() as u64 + 1) as usize;
+            line.insert(at, random_digit(rng));
+        }
+
+        document += &line;
+        document.push('\n');
+    }
+
+    Ok(document)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,7 +249,7 @@ mod tests {
         for (test, solution) in tests {
             let digits = test.chars().filter_map(|chr| chr.to_digit(10));
             let val = calibration_value(digits);
-            assert_eq!(val, solution);
+            assert_eq!(val, Some(solution));
         }
     }
 
@@ -134,7 +268,7 @@ mod tests {
         for (test, solution) in tests {
             let digits = Digits::new(test);
             let val = calibration_value(digits);
-            assert_eq!(val, solution);
+            assert_eq!(val, Some(solution));
         }
     }
 
@@ -218,4 +352,45 @@ mod tests {
         assert_eq!(digits.next(), Some(8));
         assert_eq!(digits.next(), None);
     }
+
+    #[test]
+    fn finds_words_after_trimming() {
+        let tests = [
+            ("vpinctfh9jehwthreeighninexapfour", vec![9, 3, 9, 4]),
+            ("hnine1", vec![9, 1]),
+            ("sevefourx", vec![4]),
+            ("ninine", vec![9]),
+            ("sevenine", vec![7, 9]),
+        ];
+
+        for (test, expected) in tests {
+            assert_eq!(Digits::new(test).collect::<Vec<_>>(), expected, "{test}");
+        }
+
+        let mut digits = Digits::new("xfour");
+        assert_eq!(digits.next(), Some(4));
+        assert_eq!(digits.next(), None);
+        assert_eq!(digits.next(), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "1abc2\npqr3stu8vwx\n\ntreb7uchet\n";
+        let document = Document::parse(text.as_bytes()).unwrap();
+        assert_eq!(document.to_string(), text);
+    }
+
+    #[test]
+    fn streaming() {
+        let input = "two1nine\neightwothree\nabcone2threexyz\n";
+        let document = Document::parse(input.as_bytes()).unwrap();
+
+        let (one, two) = Document::stream(input.as_bytes());
+        assert_eq!(two.unwrap(), document.part_two().unwrap());
+
+        // the second line only has digits that are spelled out
+        let e = one.unwrap_err();
+        assert_eq!(e.to_string(), "line 2 has no digits");
+        assert_eq!(e.to_string(), document.part_one().unwrap_err().to_string());
+    }
 }
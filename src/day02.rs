@@ -1,10 +1,20 @@
+use crate::checked::{self, Int};
+use crate::error::{OverflowError, ParseError};
+use crate::generate::{Knob, Knobs};
+use crate::parse::Scanner;
+use crate::rng::Rng;
+use crate::stream;
+use crate::{Solution, Streaming};
+use anyhow::ensure;
+use std::fmt;
+use std::io::BufRead;
 use std::str::FromStr;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct CubeSet {
-    red: u32,
-    green: u32,
-    blue: u32,
+    red: u64,
+    green: u64,
+    blue: u64,
 }
 
 impl CubeSet {
@@ -16,31 +26,66 @@ impl CubeSet {
         }
     }
 
-    pub fn with_red(mut self, red: u32) -> Self {
+    pub fn with_red(mut self, red: u64) -> Self {
         self.red = red;
         self
     }
 
-    pub fn with_green(mut self, green: u32) -> Self {
+    pub fn with_green(mut self, green: u64) -> Self {
         self.green = green;
         self
     }
 
-    pub fn with_blue(mut self, blue: u32) -> Self {
+    pub fn with_blue(mut self, blue: u64) -> Self {
         self.blue = blue;
         self
     }
 
-    fn total(&self) -> u32 {
-        self.red + self.green + self.blue
+    fn total(&self) -> u128 {
+        // can't overflow, unlike the sum of the u64s
+        u128::from(self.red) + u128::from(self.green) + u128::from(self.blue)
     }
 
-    pub fn power(&self) -> u32 {
-        self.red * self.green * self.blue
+    pub fn power(&self) -> Result<Int, OverflowError> {
+        checked::product([self.red, self.green, self.blue])
     }
 }
 
-#[derive(Debug)]
+/// Writes a draw like `4 red, 3 blue`, leaving out colors with no cubes.
+impl fmt::Display for CubeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let colors = [
+            ("red", self.red),
+            ("green", self.green),
+            ("blue", self.blue),
+        ];
+        let mut drawn = colors.iter().filter(|&&(_, n)| n > 0).peekable();
+
+        // a draw of no cubes still needs a color to parse
+        if drawn.peek().is_none() {
+            return write!(f, "0 red");
+        }
+
+        for (i, (color, n)) in drawn.enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{n} {color}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The cubes in the bag for part one.
+const BAG: CubeSet = CubeSet {
+    red: 12,
+    green: 13,
+    blue: 14,
+};
+
+#[derive(Debug, PartialEq)]
 pub struct Game {
     id: u32,
     revealed_subsets: Vec<CubeSet>,
@@ -95,107 +140,201 @@ impl Game {
 }
 
 impl FromStr for Game {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
+    /// Parse a game like `Game 1: 3 blue, 4 red; 1 red, 2 green`.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use parser::*;
+        let mut scanner = Scanner::new(s);
 
-        let mut buf = String::with_capacity(s.len());
+        scanner.tag("Game")?;
+        let id = scanner.integer("a game ID")?;
+        scanner.tag(":")?;
 
         let mut game = Self {
-            id: 0,
+            id,
             revealed_subsets: Vec::new(),
         };
 
-        let mut last_char_was_digit = false;
-        let mut last_token = Token::Start;
         let mut current_set = CubeSet::empty();
 
-        for c in s.chars() {
-            // if inside a number, keep adding digits
-            if c.is_ascii_digit() {
-                last_char_was_digit = true;
-                buf.push(c);
+        loop {
+            let n = scanner.integer("a number of cubes")?;
+
+            match scanner.word("a color")? {
+                "red" => current_set.red = n,
+                "green" => current_set.green = n,
+                "blue" => current_set.blue = n,
+                other => return Err(ParseError::new(s, other, "a color")),
+            }
+
+            if scanner.eat(",") {
                 continue;
             }
 
-            if !last_char_was_digit && !c.is_whitespace() {
-                buf.push(c);
+            if scanner.eat(";") {
+                game.revealed_subsets.push(current_set);
+                current_set = CubeSet::empty();
+                continue;
             }
 
-            last_char_was_digit = false;
+            if !scanner.is_empty() {
+                return Err(scanner.error("`,`, `;` or end of line"));
+            }
 
-            if let Ok(token) = parser::Token::from_str(&buf) {
-                match (last_token, token) {
-                    (Token::Game, Token::Number(n)) => {
-                        game.id = n;
-                    }
+            break;
+        }
 
-                    (Token::Number(n), Token::Color(c)) => match c {
-                        Color::Red => current_set.red = n,
-                        Color::Green => current_set.green = n,
-                        Color::Blue => current_set.blue = n,
-                    },
+        game.revealed_subsets.push(current_set);
 
-                    (_, Token::Semicolon) => {
-                        game.revealed_subsets.push(current_set);
-                        current_set = CubeSet::empty();
-                    }
+        Ok(game)
+    }
+}
 
-                    (_, _) => {}
-                }
+/// Writes a game the way it is parsed, like `Game 1: 4 red, 3 blue; 1 red, 2 green, 6 blue`.
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}:", self.id)?;
 
-                last_token = token;
-                buf.clear();
-            }
+        for (i, subset) in self.revealed_subsets.iter().enumerate() {
+            let separator = if i > 0 { ";" } else { "" };
+            write!(f, "{separator} {subset}")?;
         }
 
-        game.revealed_subsets.push(current_set);
+        Ok(())
+    }
+}
 
-        Ok(game)
+#[derive(Debug)]
+pub struct Games {
+    games: Vec<Game>,
+}
+
+impl fmt::Display for Games {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for game in &self.games {
+            writeln!(f, "{game}")?;
+        }
+
+        Ok(())
     }
 }
 
-mod parser {
-    use std::str::FromStr;
-
-    #[derive(Debug, Copy, Clone, PartialEq)]
-    pub enum Color {
-        Red,
-        Green,
-        Blue,
-    }
-
-    #[derive(Debug, Copy, Clone, PartialEq)]
-    pub enum Token {
-        Start,
-        Game,
-        Colon,
-        Comma,
-        Semicolon,
-        Color(Color),
-        Number(u32),
-    }
-
-    impl FromStr for Token {
-        type Err = anyhow::Error;
-
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            match s {
-                "Game" => Ok(Self::Game),
-                ":" => Ok(Self::Colon),
-                "," => Ok(Self::Comma),
-                ";" => Ok(Self::Semicolon),
-                "red" => Ok(Self::Color(Color::Red)),
-                "green" => Ok(Self::Color(Color::Green)),
-                "blue" => Ok(Self::Color(Color::Blue)),
-                other => {
-                    let n = u32::from_str(other)?;
-                    Ok(Self::Number(n))
-                }
-            }
+impl Solution for Games {
+    type PartOne = Int;
+    type PartTwo = Int;
+
+    fn parse<R>(reader: R) -> anyhow::Result<Self>
+    where
+        R: BufRead,
+    {
+        let mut games = Vec::new();
+
+        for (idx, line) in reader.lines().enumerate() {
+            let game = Game::from_str(&line?).map_err(|e| e.with_line(idx + 1))?;
+            games.push(game);
         }
+
+        Ok(Self { games })
+    }
+
+    /// Sum of the IDs of all games that are possible with the cubes in `BAG`.
+    fn part_one(&self) -> anyhow::Result<Int> {
+        let ids = self
+            .games
+            .iter()
+            .filter(|game| game.is_valid(&BAG))
+            .map(Game::id);
+
+        Ok(checked::sum(ids)?)
+    }
+
+    fn part_two(&self) -> anyhow::Result<Int> {
+        let powers = self
+            .games
+            .iter()
+            .map(|game| game.minimum_configuration().power());
+
+        Ok(checked::try_sum(powers)?)
+    }
+}
+
+impl Streaming for Games {
+    fn stream<R>(reader: R) -> (anyhow::Result<Int>, anyhow::Result<Int>)
+    where
+        R: BufRead,
+    {
+        stream::split(stream::fold_lines(
+            reader,
+            (0, 0),
+            |(ids, powers), n, line| {
+                let game = Game::from_str(line).map_err(|e| e.with_line(n))?;
+                let id = if game.is_valid(&BAG) { game.id } else { 0 };
+
+                let power = game.minimum_configuration().power()?;
+
+                Ok((
+                    checked::add(ids, Int::from(id))?,
+                    checked::add(powers, power)?,
+                ))
+            },
+        ))
+    }
+}
+
+pub const KNOBS: &[Knob] = &[
+    Knob::new("games", 100, "number of games"),
+    Knob::new("draws", 6, "maximum number of draws in a game"),
+    Knob::new(
+        "cubes",
+        20,
+        "maximum number of cubes of one color in a draw",
+    ),
+];
+
+/// Generate a record of games.
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> anyhow::Result<String> {
+    let games = knobs.get("games");
+    let draws = knobs.get("draws");
+    let cubes = knobs.get("cubes");
+
+    ensure!(draws > 0 && cubes > 0, "games need at least one draw");
+
+    // the powers of part two are the largest numbers
+    let fits = Int::from(cubes)
+        .checked_pow(3)
+        .and_then(|power| power.checked_mul(Int::from(games)))
+        .is_some();
+    ensure!(
+        fits,
+        "too many games or cubes for the answers to fit in {} bits",
+        Int::BITS
+    );
+
+    let mut record = String::new();
+
+    for id in 1..=games {
+        let n = rng.range_inclusive(1..=draws);
+
+        let draws = (0..n)
+            .map(|_| {
+                let mut colors = ["red", "green", "blue"];
+                rng.shuffle(&mut colors);
+
+                let n = rng.range_inclusive(1..=3) as usize;
+
+                colors[..n]
+                    .iter()
+                    .map(|color| format!("{} {color}", rng.range_inclusive(1..=cubes)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        record += &format!("Game {id}: {draws}\n");
     }
+
+    Ok(record)
 }
 
 #[cfg(test)]
@@ -236,4 +375,35 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn round_trip() {
+        let test = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green; 0 blue";
+        let game = Game::from_str(test).unwrap();
+
+        let text = game.to_string();
+        assert_eq!(
+            text,
+            "Game 1: 4 red, 3 blue; 1 red, 2 green, 6 blue; 2 green; 0 red"
+        );
+        assert_eq!(Game::from_str(&text).unwrap(), game);
+    }
+
+    #[test]
+    fn invalid_games() {
+        let e = Game::from_str("Game 1: 3 blue, 4 purple").unwrap_err();
+        assert_eq!(e.column(), 19);
+        assert_eq!(e.found(), "purple");
+
+        let e = Game::from_str("Game 1: 3 blue 4 red").unwrap_err();
+        assert_eq!(e.found(), "4");
+        assert_eq!(e.expected(), "`,`, `;` or end of line");
+
+        let e = Game::from_str("Game 1: 3 blue,").unwrap_err();
+        assert_eq!(e.found(), "");
+        assert_eq!(e.expected(), "a number of cubes");
+
+        let e = Game::from_str("Gaem 1: 3 blue").unwrap_err();
+        assert_eq!(e.column(), 1);
+    }
 }
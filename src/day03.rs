@@ -1,29 +1,28 @@
-use anyhow::Result;
+use crate::checked::{self, Int};
+use crate::error::ParseError;
+use crate::generate::{Knob, Knobs};
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::normalize::Layout;
+use crate::render::{Canvas, Cell, Color};
+use crate::rng::Rng;
+use crate::Solution;
+use anyhow::{ensure, Result};
 use std::collections::HashSet;
+use std::fmt;
 use std::hash::Hash;
 use std::io::prelude::*;
 use std::ops::Range;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub struct FixedAddress {
-    x: u32,
-    y: u32,
-}
-
-impl FixedAddress {
-    pub fn new(x: u32, y: u32) -> Self {
-        Self { x, y }
-    }
-}
-
+/// The cells taken up by a number, which is always on a single row.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Address {
-    x: Range<u32>,
-    y: u32,
+    x: Range<i64>,
+    y: i64,
 }
 
 impl Address {
-    fn new(x_start: u32, x_end: u32, y: u32) -> Self {
+    fn new(x_start: i64, x_end: i64, y: i64) -> Self {
         Self {
             x: Range {
                 start: x_start,
@@ -33,24 +32,10 @@ impl Address {
         }
     }
 
-    fn is_adjacent_to(&self, other: &FixedAddress) -> bool {
-        if self.y.abs_diff(other.y) > 1 {
-            return false;
-        }
-
-        if self.x.contains(&other.x) {
-            return true;
-        }
-
-        if self.x.start > other.x && (self.x.start - other.x) <= 1 {
-            return true;
-        }
-
-        if self.x.end <= other.x && (other.x - self.x.end) == 0 {
-            return true;
-        }
-
-        false
+    fn is_adjacent_to(&self, other: Point) -> bool {
+        self.x
+            .clone()
+            .any(|x| Point::new(x, self.y).chebyshev(other) <= 1)
     }
 }
 
@@ -63,19 +48,21 @@ pub struct Number {
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Symbol {
     sym: char,
-    addr: FixedAddress,
+    addr: Point,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Gear {
     symbol: Symbol,
-    pub ratio: u32,
+    pub ratio: Int,
 }
 
 #[derive(Debug)]
 pub struct EngineSchematic {
     numbers: Vec<Number>,
     symbols: Vec<Symbol>,
+    width: usize,
+    height: usize,
 }
 
 impl EngineSchematic {
@@ -86,56 +73,60 @@ impl EngineSchematic {
         let mut numbers = Vec::new();
         let mut symbols = Vec::new();
 
-        let mut in_number = false;
-        let mut num_x_start = 0;
-
-        let mut y = 0;
-
-        let mut buf = String::new();
-
-        for line in reader.lines() {
-            let line = line?;
+        let grid = Grid::parse(reader, |c| {
+            if c.is_whitespace() || c.is_control() {
+                Err(ParseError::unexpected(c, "a digit, `.` or a symbol"))
+            } else {
+                Ok(c)
+            }
+        })?;
 
+        for (y, row) in grid.rows().enumerate() {
             let mut x = 0;
 
-            for c in line.chars() {
+            while x < row.len() {
+                let c = row[x];
+
                 if c.is_ascii_digit() {
-                    buf.push(c);
+                    let x_start = x;
 
-                    if !in_number {
-                        in_number = true;
-                        num_x_start = x;
+                    while x < row.len() && row[x].is_ascii_digit() {
+                        x += 1;
                     }
 
-                    x += 1;
-                    continue;
-                }
+                    let digits = row[x_start..x].iter().collect::<String>();
 
-                if in_number {
-                    let num = buf.parse::<u32>()?;
-                    let addr = Address::new(num_x_start, x, y);
-                    numbers.push(Number { num, addr });
+                    let num = digits.parse::<u32>().map_err(|_| {
+                        // the digits are ASCII, but the symbols before them might not be
+                        let line = row.iter().collect::<String>();
+                        let start = row[..x_start].iter().map(|c| c.len_utf8()).sum();
 
-                    in_number = false;
-                    buf.clear();
-                }
+                        ParseError::at(&line, start, start + digits.len(), "a smaller number")
+                            .with_line(y + 1)
+                    })?;
+
+                    let addr = Address::new(x_start as i64, x as i64, y as i64);
+                    numbers.push(Number { num, addr });
 
-                if c == '.' {
-                    x += 1;
                     continue;
                 }
 
-                // found a symbol
-                let addr = FixedAddress::new(x, y);
-                symbols.push(Symbol { sym: c, addr });
+                if c != '.' {
+                    // found a symbol
+                    let addr = Point::new(x as i64, y as i64);
+                    symbols.push(Symbol { sym: c, addr });
+                }
 
                 x += 1;
             }
-
-            y += 1;
         }
 
-        Ok(Self { numbers, symbols })
+        Ok(Self {
+            numbers,
+            symbols,
+            width: grid.width(),
+            height: grid.height(),
+        })
     }
 
     pub fn part_numbers(&self) -> HashSet<Number> {
@@ -143,7 +134,7 @@ impl EngineSchematic {
 
         for symbol in self.symbols.iter() {
             for number in self.numbers.iter() {
-                if number.addr.is_adjacent_to(&symbol.addr) {
+                if number.addr.is_adjacent_to(symbol.addr) {
                     part_nos.insert(number.clone());
                 }
             }
@@ -164,7 +155,7 @@ impl EngineSchematic {
             let mut num_b = None;
 
             for number in self.numbers.iter() {
-                if number.addr.is_adjacent_to(&symbol.addr) {
+                if number.addr.is_adjacent_to(symbol.addr) {
                     if num_a.is_none() {
                         num_a.replace(number.num);
                     } else if num_b.is_none() {
@@ -177,13 +168,243 @@ impl EngineSchematic {
             }
 
             if let (Some(a), Some(b)) = (num_a, num_b) {
+                // the product of two u32s always fits
                 gears.push(Gear {
                     symbol: *symbol,
-                    ratio: a * b,
+                    ratio: Int::from(a) * Int::from(b),
                 });
             }
         }
 
         gears
     }
+
+    /// Draw the schematic with part numbers in green, other numbers in red and gears in yellow.
+    fn draw(&self) -> Canvas {
+        let mut canvas = Grid::filled(self.width, self.height, Cell::new('.', Color::Dim));
+        let part_numbers = self.part_numbers();
+
+        for number in self.numbers.iter() {
+            let color = if part_numbers.contains(number) {
+                Color::Green
+            } else {
+                Color::Red
+            };
+
+            // keep any leading zeros
+            let width = (number.addr.x.end - number.addr.x.start) as usize;
+            let digits = format!("{:0width$}", number.num);
+
+            for (x, c) in number.addr.x.clone().zip(digits.chars()) {
+                canvas[Point::new(x, number.addr.y)] = Cell::new(c, color);
+            }
+        }
+
+        for symbol in self.symbols.iter() {
+            canvas[symbol.addr] = Cell::new(symbol.sym, Color::Cyan);
+        }
+
+        for gear in self.gears() {
+            canvas[gear.symbol.addr].color = Color::Yellow;
+        }
+
+        canvas
+    }
+}
+
+/// Writes the schematic back out, numbers with any leading zeros they had.
+impl fmt::Display for EngineSchematic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut grid = Grid::filled(self.width, self.height, '.');
+
+        for number in self.numbers.iter() {
+            let width = (number.addr.x.end - number.addr.x.start) as usize;
+            let digits = format!("{:0width$}", number.num);
+
+            for (x, c) in number.addr.x.clone().zip(digits.chars()) {
+                grid[Point::new(x, number.addr.y)] = c;
+            }
+        }
+
+        for symbol in self.symbols.iter() {
+            grid[symbol.addr] = symbol.sym;
+        }
+
+        write!(f, "{grid}")
+    }
+}
+
+impl Solution for EngineSchematic {
+    type PartOne = Int;
+    type PartTwo = Int;
+    const LAYOUT: Layout = Layout::Grid;
+
+    fn parse<R>(reader: R) -> Result<Self>
+    where
+        R: BufRead,
+    {
+        Self::load(reader)
+    }
+
+    fn part_one(&self) -> Result<Int> {
+        Ok(checked::sum(
+            self.part_numbers().iter().map(|part_no| part_no.num),
+        )?)
+    }
+
+    fn part_two(&self) -> Result<Int> {
+        Ok(checked::sum(self.gears().iter().map(|gear| gear.ratio))?)
+    }
+
+    fn render(&self) -> Option<Canvas> {
+        Some(self.draw())
+    }
+}
+
+pub const KNOBS: &[Knob] = &[
+    Knob::new("width", 140, "width of the schematic"),
+    Knob::new("height", 140, "height of the schematic"),
+    Knob::new("numbers", 1200, "number of part numbers, at most"),
+    Knob::new("symbols", 750, "number of symbols, at most"),
+];
+
+const SYMBOLS: [char; 10] = ['*', '#', '+', '$', '/', '@', '%', '=', '&', '-'];
+
+/// Generate an engine schematic, placing numbers and symbols at random free positions.
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> Result<String> {
+    let width = knobs.get("width");
+    let height = knobs.get("height");
+    let numbers = knobs.get("numbers");
+    let symbols = knobs.get("symbols");
+
+    ensure!(width > 0 && height > 0, "the schematic can't be empty");
+
+    // every symbol could be a gear with two three-digit numbers
+    let fits = Int::from(symbols).checked_mul(999 * 999).is_some();
+    ensure!(
+        fits,
+        "too many symbols for the answers to fit in {} bits",
+        Int::BITS
+    );
+
+    let mut grid = Grid::filled(width as usize, height as usize, '.');
+
+    // give up on placing more once the schematic is too crowded
+    let mut placed = 0;
+    let mut attempts = 0;
+
+    while placed < numbers && attempts < numbers * 20 {
+        attempts += 1;
+
+        let len = rng.range_inclusive(1..=width.min(3));
+        let x = rng.below(width - len + 1) as i64;
+        let y = rng.below(height) as i64;
+
+        // leave a gap to other numbers, so that they don't run into each other
+        let free = (x - 1..=x + len as i64)
+            .map(|x| Point::new(x, y))
+            .all(|p| grid.get(p).is_none_or(|&c| c == '.'));
+
+        if !free {
+            continue;
+        }
+
+        let n = rng.range(10u64.pow(len as u32 - 1)..10u64.pow(len as u32));
+
+        for (i, digit) in n.to_string().chars().enumerate() {
+            grid[Point::new(x + i as i64, y)] = digit;
+        }
+
+        placed += 1;
+    }
+
+    let mut placed = 0;
+    let mut attempts = 0;
+
+    while placed < symbols && attempts < symbols * 20 {
+        attempts += 1;
+
+        let p = Point::new(rng.below(width) as i64, rng.below(height) as i64);
+
+        if grid[p] == '.' {
+            grid[p] = *rng.choose(&SYMBOLS);
+            placed += 1;
+        }
+    }
+
+    Ok(grid.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    static EXAMPLE: &str = include_str!("../inputs/day03/examples/example");
+
+    fn load(s: &str) -> Result<EngineSchematic> {
+        EngineSchematic::load(BufReader::new(s.as_bytes()))
+    }
+
+    #[test]
+    fn example() {
+        let schematic = load(EXAMPLE).unwrap();
+
+        assert_eq!(schematic.part_one().unwrap(), 4361);
+        assert_eq!(schematic.part_two().unwrap(), 467835);
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(load(EXAMPLE).unwrap().to_string(), EXAMPLE);
+
+        let schematic = "007*\n..#.\n";
+        assert_eq!(load(schematic).unwrap().to_string(), schematic);
+    }
+
+    #[test]
+    fn number_at_end_of_line() {
+        let schematic = load("*...12\n....99\n").unwrap();
+        assert_eq!(schematic.part_one().unwrap(), 0);
+
+        let schematic = load("...*12\n...100\n").unwrap();
+        assert_eq!(schematic.part_one().unwrap(), 112);
+    }
+
+    #[test]
+    fn render() {
+        let canvas = load(EXAMPLE).unwrap().render().unwrap();
+        assert_eq!(canvas.to_string(), EXAMPLE);
+
+        let colored = |color| {
+            canvas
+                .iter()
+                .filter(|(_, cell)| cell.color == color)
+                .map(|(_, cell)| cell.c)
+                .collect::<String>()
+        };
+
+        // 114 and 58 are not next to a symbol, and the two gears are the first and last `*`
+        assert_eq!(colored(Color::Red), "11458");
+        assert_eq!(colored(Color::Yellow), "**");
+        assert_eq!(colored(Color::Cyan), "#*+$");
+
+        let leading_zero = load("007*..\n").unwrap().render().unwrap();
+        assert_eq!(leading_zero.to_string(), "007*..\n");
+    }
+
+    #[test]
+    fn invalid_input() {
+        let e = load("467..114..\n...*. ....\n").unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+
+        assert_eq!(e.line(), Some(2));
+        assert_eq!(e.column(), 6);
+
+        let e = load("..4294967296\n............\n").unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+
+        assert_eq!(e.found(), "4294967296");
+        assert_eq!(e.column(), 3);
+    }
 }
@@ -1,8 +1,17 @@
-use anyhow::anyhow;
-use std::collections::HashSet;
+use crate::checked::{self, Int};
+use crate::error::{OverflowError, ParseError};
+use crate::generate::{Knob, Knobs};
+use crate::parse::Scanner;
+use crate::rng::Rng;
+use crate::stream;
+use crate::{Solution, Streaming};
+use anyhow::ensure;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::io::BufRead;
 use std::str::FromStr;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Card {
     id: u32,
     winning: HashSet<u32>,
@@ -29,71 +38,278 @@ impl Card {
             .count() as u32
     }
 
-    pub fn points(&self) -> u32 {
+    pub fn points(&self) -> Result<Int, OverflowError> {
         let matches = self.matches();
         if matches == 0 {
-            0
+            Ok(0)
         } else {
-            2u32.pow(matches - 1)
+            checked::pow(2, matches - 1)
         }
     }
 }
 
 impl FromStr for Card {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
+    /// Parse a card like `Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53`.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut buf = String::new();
         let mut card = Self::empty();
 
-        let mut found_id = false;
-        let mut found_pipe = false;
+        let mut scanner = Scanner::new(s);
+
+        scanner.tag("Card")?;
+        card.id = scanner.integer("a card ID")?;
+        scanner.tag(":")?;
+
+        card.winning = scanner.until("|")?.integers()?.into_iter().collect();
+        card.have = scanner.integers()?.into_iter().collect();
+
+        Ok(card)
+    }
+}
+
+/// Writes a card like `Card 1: 17 41 48 83 86 | 6 9 17 31 48 53 83 86`, with its numbers sorted.
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn sorted(numbers: &HashSet<u32>) -> Vec<u32> {
+            let mut numbers = numbers.iter().copied().collect::<Vec<_>>();
+            numbers.sort_unstable();
+            numbers
+        }
+
+        write!(f, "Card {}:", self.id)?;
+
+        for n in sorted(&self.winning) {
+            write!(f, " {n}")?;
+        }
+
+        write!(f, " |")?;
+
+        for n in sorted(&self.have) {
+            write!(f, " {n}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct Scratchcards {
+    cards: Vec<Card>,
+}
+
+impl fmt::Display for Scratchcards {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for card in &self.cards {
+            writeln!(f, "{card}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Error for copies won of cards past the last card, after `lines` lines of cards.
+fn past_the_end(lines: usize) -> ParseError {
+    ParseError::missing_line(lines + 1, "a card won by an earlier card")
+}
+
+impl Scratchcards {
+    /// The total number of cards we end up with, counting originals and copies.
+    ///
+    /// Cards win copies of the cards after them in the pile, whatever their IDs.
+    pub fn total_cards(&self) -> anyhow::Result<Int> {
+        // how many times we have each card
+        let mut counts = self.cards.iter().map(|_| 1).collect::<Vec<Int>>();
+        let mut missing = false;
+
+        for (i, card) in self.cards.iter().enumerate() {
+            let last = checked::add(i, card.matches() as usize)?;
 
-        for c in s.chars() {
-            if c == '|' {
-                found_pipe = true;
-            } else if c.is_ascii_digit() {
-                buf.push(c);
-                continue;
+            if last >= counts.len() {
+                missing = true;
             }
 
-            if buf.is_empty() {
-                continue;
+            for j in (i + 1)..=last.min(counts.len() - 1) {
+                // Add each card we win as many times as we have the winning card.
+                // Say we are looking at card 5 and it wins us card 6. But, due to prior cards, we
+                // already have 3 of card 5. So we must add 3 of card 6 here.
+                // This works out because no card can ever win a card below it, so we can't forget
+                // to count any.
+                counts[j] = checked::add(counts[j], counts[i])?;
             }
+        }
 
-            let result = buf.parse::<u32>();
-            buf.clear();
+        let total = checked::sum(counts)?;
 
-            let num = match result {
-                Ok(num) => num,
-                Err(_) => continue,
-            };
+        if missing {
+            return Err(past_the_end(self.cards.len()).into());
+        }
 
-            match (found_id, found_pipe) {
-                (false, false) => {
-                    card.id = num;
-                    found_id = true;
-                }
-                (true, false) => {
-                    card.winning.insert(num);
-                }
-                (true, true) => {
-                    card.have.insert(num);
+        Ok(total)
+    }
+}
+
+impl Solution for Scratchcards {
+    type PartOne = Int;
+    type PartTwo = Int;
+
+    fn parse<R>(reader: R) -> anyhow::Result<Self>
+    where
+        R: BufRead,
+    {
+        let mut cards = Vec::new();
+
+        for (idx, line) in reader.lines().enumerate() {
+            let card = line?.parse::<Card>().map_err(|e| e.with_line(idx + 1))?;
+            cards.push(card);
+        }
+
+        Ok(Self { cards })
+    }
+
+    fn part_one(&self) -> anyhow::Result<Int> {
+        Ok(checked::try_sum(self.cards.iter().map(Card::points))?)
+    }
+
+    fn part_two(&self) -> anyhow::Result<Int> {
+        self.total_cards()
+    }
+}
+
+impl Streaming for Scratchcards {
+    fn stream<R>(reader: R) -> (anyhow::Result<Int>, anyhow::Result<Int>)
+    where
+        R: BufRead,
+    {
+        // copies won of the next cards, so only as many as a card can have matches
+        let mut won = VecDeque::new();
+        let mut lines = 0;
+
+        let (points, cards) = stream::split(stream::fold_lines(
+            reader,
+            (0, 0),
+            |(points, cards), n, line| {
+                let card = line.parse::<Card>().map_err(|e| e.with_line(n))?;
+                let copies = checked::add(1, won.pop_front().unwrap_or(0))?;
+
+                for i in 0..card.matches() as usize {
+                    match won.get_mut(i) {
+                        Some(count) => *count = checked::add(*count, copies)?,
+                        None => won.push_back(copies),
+                    }
                 }
-                (false, true) => return Err(anyhow!("Invalid input!")),
+
+                lines = n;
+
+                Ok((
+                    checked::add(points, card.points()?)?,
+                    checked::add(cards, copies)?,
+                ))
+            },
+        ));
+
+        // copies still to be won are of cards past the last one
+        let cards = cards.and_then(|cards| match won.is_empty() {
+            true => Ok(cards),
+            false => Err(past_the_end(lines).into()),
+        });
+
+        (points, cards)
+    }
+}
+
+pub const KNOBS: &[Knob] = &[
+    Knob::new("cards", 200, "number of cards"),
+    Knob::new("winning", 10, "number of winning numbers on a card"),
+    Knob::new("have", 25, "number of numbers we have on a card"),
+];
+
+/// Generate a pile of scratchcards.
+///
+/// Cards mostly win little, as copies of cards add up quickly: the number of matches is lowered
+/// where needed to keep the answers within [`Int`].
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> anyhow::Result<String> {
+    let cards = knobs.get("cards") as usize;
+    let winning = knobs.get("winning") as usize;
+    let have = knobs.get("have") as usize;
+
+    ensure!(
+        winning + have <= 99,
+        "cards only have room for 99 different numbers"
+    );
+
+    let max_copies = Int::MAX / cards.max(1) as Int;
+    let mut copies: Vec<Int> = vec![1; cards];
+    let mut points: Int = 0;
+
+    let mut pile = String::new();
+
+    for id in 0..cards {
+        let max = winning.min(have).min(cards - id - 1);
+
+        let mut matches = if rng.chance(0.5) {
+            0
+        } else {
+            rng.range_inclusive(0..=max as u64) as usize
+        };
+
+        loop {
+            let card_points = match matches {
+                0 => Some(0),
+                matches => Int::checked_pow(2, matches as u32 - 1),
             };
+
+            let total = card_points.and_then(|card_points| points.checked_add(card_points));
+            let fits = copies[id + 1..=id + matches]
+                .iter()
+                .all(|&n| n + copies[id] <= max_copies);
+
+            if let (Some(total), true) = (total, fits) {
+                points = total;
+                break;
+            }
+
+            matches -= 1;
         }
 
-        let num = buf.parse::<u32>()?;
-        card.have.insert(num);
+        let won = copies[id];
 
-        Ok(card)
+        for n in copies[id + 1..=id + matches].iter_mut() {
+            *n += won;
+        }
+
+        let mut numbers = (1..=99).collect::<Vec<u32>>();
+        rng.shuffle(&mut numbers);
+
+        let winning_numbers = &numbers[..winning];
+
+        let mut have_numbers = winning_numbers[..matches].to_vec();
+        have_numbers.extend_from_slice(&numbers[winning..winning + have - matches]);
+        rng.shuffle(&mut have_numbers);
+
+        let format = |numbers: &[u32]| {
+            numbers
+                .iter()
+                .map(|n| format!("{n:>2}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        pile += &format!(
+            "Card {:>3}: {} | {}\n",
+            id + 1,
+            format(winning_numbers),
+            format(&have_numbers)
+        );
     }
+
+    Ok(pile)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Card;
+    use super::*;
+    use std::io::BufReader;
 
     #[test]
     fn provided_tests_part_one() {
@@ -110,7 +326,86 @@ mod tests {
             let card = test.parse::<Card>().unwrap();
             println!("{card:?}");
             assert_eq!(card.id, idx as u32 + 1);
-            assert_eq!(card.points(), *solution);
+            assert_eq!(card.points().unwrap(), *solution);
         }
     }
+
+    #[test]
+    fn too_many_points() {
+        let numbers = (1..=140)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let card = format!("Card 1: {numbers} | {numbers}")
+            .parse::<Card>()
+            .unwrap();
+
+        assert_eq!(card.matches(), 140);
+        assert!(card.points().is_err());
+
+        let cards = Scratchcards::parse(format!("Card 1: {numbers} | {numbers}\n").as_bytes());
+        let e = cards.unwrap().part_one().unwrap_err();
+        assert!(e.downcast_ref::<OverflowError>().is_some());
+    }
+
+    #[test]
+    fn provided_tests_part_two() {
+        let example = include_str!("../inputs/day04/examples/example");
+
+        let cards = Scratchcards::parse(BufReader::new(example.as_bytes())).unwrap();
+        assert_eq!(cards.part_two().unwrap(), 30);
+    }
+
+    #[test]
+    fn cards_count_by_position() {
+        let missing = "line 2: expected a card won by an earlier card, found end of input";
+
+        let piles = [
+            ("Card 2: 1 | 1\nCard 1: 1 | 2\n", 1, Ok(3)),
+            ("Card 0: 1 | 1\nCard 1: 1 | 2\n", 1, Ok(3)),
+            ("Card 4294967295: 1 | 1\nCard 7: 1 | 2\n", 1, Ok(3)),
+            ("Card 1: 1 2 | 1 2\n", 2, Err(missing)),
+        ];
+
+        for (pile, points, cards) in piles {
+            let parsed = Scratchcards::parse(pile.as_bytes()).unwrap();
+            let (streamed_points, streamed_cards) = Scratchcards::stream(pile.as_bytes());
+
+            assert_eq!(parsed.part_one().unwrap(), points, "{pile}");
+            assert_eq!(streamed_points.unwrap(), points, "{pile}");
+
+            let cards = cards.map_err(str::to_owned);
+            assert_eq!(
+                parsed.part_two().map_err(|e| e.to_string()),
+                cards,
+                "{pile}"
+            );
+            assert_eq!(streamed_cards.map_err(|e| e.to_string()), cards, "{pile}");
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let card = "Card   3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1"
+            .parse::<Card>()
+            .unwrap();
+
+        let text = card.to_string();
+        assert_eq!(text, "Card 3: 1 21 44 53 59 | 1 14 16 21 63 69 72 82");
+        assert_eq!(text.parse::<Card>().unwrap(), card);
+    }
+
+    #[test]
+    fn invalid_cards() {
+        let e = "Card 1: 41 48 | 83 x6 17".parse::<Card>().unwrap_err();
+        assert_eq!(e.column(), 20);
+        assert_eq!(e.expected(), "a number");
+
+        let e = "Card 1: 41 48 83 86 17".parse::<Card>().unwrap_err();
+        assert_eq!(e.expected(), "`|`");
+
+        let e = "Crad 1: 41 | 83".parse::<Card>().unwrap_err();
+        assert_eq!(e.column(), 1);
+        assert_eq!(e.found(), "Crad");
+    }
 }
@@ -1,42 +1,45 @@
-use anyhow::{anyhow, Context, Result};
+use crate::checked::Int;
+use crate::error::{OverflowError, ParseError};
+use crate::generate::{Knob, Knobs};
+use crate::interval::{Interval, IntervalSet};
+use crate::parse::Scanner;
+use crate::rng::Rng;
+use crate::Solution;
+use anyhow::{ensure, Context, Result};
+use std::fmt;
 use std::io::BufRead;
 
+/// Maps the integers in `src` to as many integers starting at `dst`.
 #[derive(Debug)]
 struct Range {
-    dst: u64,
-    src: u64,
-    len: u64,
+    src: Interval<Int>,
+    dst: Int,
 }
 
 impl Range {
-    fn contains_src(&self, src: u64) -> bool {
-        src >= self.src && src < (self.src + self.len)
+    fn dst(&self) -> Interval<Int> {
+        // parsing made sure that this fits
+        Interval::with_len(self.dst, self.src.len())
     }
 
-    fn contains_dst(&self, dst: u64) -> bool {
-        dst >= self.dst && dst < (self.dst + self.len)
-    }
-
-    fn convert_down(&self, src: u64) -> u64 {
-        debug_assert!(self.contains_src(src));
+    fn convert_down(&self, src: Int) -> Int {
+        debug_assert!(self.src.contains(src));
 
-        let offset = src - self.src;
+        let offset = src - self.src.start();
         self.dst + offset
     }
 
-    fn convert_up(&self, dst: u64) -> u64 {
-        debug_assert!(self.contains_dst(dst));
+    fn convert_up(&self, dst: Int) -> Int {
+        debug_assert!(self.dst().contains(dst));
 
         let offset = dst - self.dst;
-        self.src + offset
+        self.src.start() + offset
     }
 }
 
 #[derive(Debug)]
 struct ConversionMap {
-    #[allow(unused)]
     from: String,
-    #[allow(unused)]
     to: String,
 
     ranges: Vec<Range>,
@@ -55,13 +58,13 @@ impl ConversionMap {
         }
     }
 
-    fn add_range(&mut self, dst: u64, src: u64, len: u64) {
-        self.ranges.push(Range { dst, src, len });
+    fn add_range(&mut self, dst: Int, src: Interval<Int>) {
+        self.ranges.push(Range { src, dst });
     }
 
-    fn convert_down(&self, n: u64) -> u64 {
+    fn convert_down(&self, n: Int) -> Int {
         for range in self.ranges.iter() {
-            if range.contains_src(n) {
+            if range.src.contains(n) {
                 return range.convert_down(n);
             }
         }
@@ -69,120 +72,153 @@ impl ConversionMap {
         n
     }
 
-    fn convert_up(&self, n: u64) -> u64 {
+    fn convert_up(&self, n: Int) -> Int {
         for range in self.ranges.iter() {
-            if range.contains_dst(n) {
+            if range.dst().contains(n) {
                 return range.convert_up(n);
             }
         }
 
         n
     }
-}
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord)]
-struct SeedRange {
-    start: u64,
-    len: u64,
-}
+    /// Convert a whole set of numbers at once.
+    ///
+    /// Like [`ConversionMap::convert_down`], numbers in more than one source range are converted
+    /// by the first of them.
+    fn convert_set(&self, set: &IntervalSet<Int>) -> Result<IntervalSet<Int>, OverflowError> {
+        let mut converted = IntervalSet::new();
+        let mut unmapped = set.clone();
 
-impl SeedRange {
-    fn new(start: u64, len: u64) -> Self {
-        Self { start, len }
-    }
+        for range in self.ranges.iter() {
+            let src = IntervalSet::from_iter([range.src]);
+
+            let mapped = unmapped
+                .intersection(&src)
+                .shift(range.src.start(), range.dst)?;
+            converted = converted.union(&mapped);
 
-    fn contains(&self, n: u64) -> bool {
-        n >= self.start && n < (self.start + self.len)
+            unmapped = unmapped.difference(&src);
+        }
+
+        // numbers not in any source range map to themselves
+        Ok(converted.union(&unmapped))
     }
 }
 
 #[derive(Debug)]
 pub struct Almanac {
-    seeds: Vec<u64>,
-    seed_ranges: Vec<SeedRange>,
+    seeds: Vec<Int>,
+    seed_ranges: IntervalSet<Int>,
     maps: Vec<ConversionMap>,
 }
 
 impl Almanac {
-    fn convert_down(mut seed: u64, maps: &[ConversionMap]) -> u64 {
-        for map in maps {
+    /// The location that `seed` ends up at.
+    pub fn location(&self, mut seed: Int) -> Int {
+        for map in self.maps.iter() {
             seed = map.convert_down(seed);
         }
 
         seed
     }
 
-    fn convert_up(mut location: u64, maps: &[ConversionMap]) -> u64 {
-        for map in maps.iter().rev() {
+    /// Convert `location` back up through the maps, undoing [`Almanac::location`].
+    ///
+    /// Where a map sends both an unmapped number and a range to the same place, this picks the
+    /// one from the range.
+    pub fn seed(&self, mut location: Int) -> Int {
+        for map in self.maps.iter().rev() {
             location = map.convert_up(location);
         }
 
         location
     }
+}
 
-    /// Convert all seeds down to locations and return the minimum of those locations.
-    pub fn part_one(&self) -> u64 {
-        self.seeds
-            .iter()
-            .map(|seed| Self::convert_down(*seed, &self.maps))
-            .min()
-            .unwrap()
-    }
+/// Writes the seeds, then each map under its header, as in the puzzle input.
+impl fmt::Display for Almanac {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seeds:")?;
 
-    /// Keep guessing locations, starting at 0, and convert backwards until a valid seed is found.
-    ///
-    /// The first location that maps to a valid seed must be the minimum.
-    pub fn part_two(&mut self) -> u64 {
-        for location in 0..u64::max_value() {
-            let seed = Self::convert_up(location, &self.maps);
+        for seed in self.seeds.iter() {
+            write!(f, " {seed}")?;
+        }
+
+        writeln!(f)?;
 
-            if self.seed_ranges.iter().any(|sr| sr.contains(seed)) {
-                return location;
+        for map in self.maps.iter() {
+            writeln!(f)?;
+            writeln!(f, "{}-to-{} map:", map.from, map.to)?;
+
+            for range in map.ranges.iter() {
+                writeln!(f, "{} {} {}", range.dst, range.src.start(), range.src.len())?;
             }
         }
 
-        panic!()
+        Ok(())
     }
+}
 
-    pub fn parse<R>(reader: R) -> Result<Self>
+impl Solution for Almanac {
+    type PartOne = Int;
+    type PartTwo = Int;
+
+    fn parse<R>(reader: R) -> Result<Self>
     where
         R: BufRead,
     {
         let mut seeds = Vec::new();
-        let mut seed_ranges = Vec::new();
+        let mut seed_ranges = IntervalSet::new();
         let mut maps = Vec::new();
 
         let mut current_map = None;
+        let mut n_lines = 0;
 
-        for line in reader.lines() {
+        for (idx, line) in reader.lines().enumerate() {
             let line = line?;
+            let line_no = idx + 1;
+            n_lines = line_no;
 
             if line.is_empty() || line.chars().all(char::is_whitespace) {
                 continue;
             }
 
-            if line.starts_with("seeds:") {
+            let at_line = |e: ParseError| e.with_line(line_no);
+            let mut scanner = Scanner::new(&line);
+
+            if scanner.eat("seeds:") {
                 // part one, single seeds
-                for seed in line.split(' ').skip(1).map(str::parse::<u64>) {
-                    seeds.push(seed?);
-                }
+                seeds = scanner.integers().map_err(at_line)?;
 
                 // part two, seed ranges
-                let mut start = None;
-                for n in line.split(' ').skip(1).map(str::parse::<u64>) {
-                    match start.take() {
-                        Some(start) => seed_ranges.push(SeedRange::new(start, n?)),
-                        None => start = Some(n?),
-                    }
+                if !seeds.len().is_multiple_of(2) {
+                    let e = ParseError::at_end(&line, "the length of the last seed range");
+                    return Err(at_line(e).into());
+                }
+
+                for pair in seeds.chunks(2) {
+                    let range = interval(pair[0], pair[1]).ok_or_else(|| {
+                        at_line(ParseError::new(
+                            &line,
+                            &line,
+                            format!("seed ranges within {} bits", Int::BITS),
+                        ))
+                    })?;
+                    seed_ranges.insert(range);
                 }
 
                 continue;
             }
 
-            if line.ends_with("map:") {
-                let mut name = line.split(' ').next().context("invalid input")?.split('-');
-                let from = name.next().context("invalid map name")?;
-                let to = name.nth(1).context("invalid map name")?;
+            if let Some(name) = line.strip_suffix(" map:") {
+                let (from, to) = name.split_once("-to-").ok_or_else(|| {
+                    at_line(ParseError::new(
+                        &line,
+                        name,
+                        "a map name like `seed-to-soil`",
+                    ))
+                })?;
 
                 let next_map = ConversionMap::new(from, to);
 
@@ -193,20 +229,32 @@ impl Almanac {
                 continue;
             }
 
-            let mut values = line.split(' ');
-            let dst = values.next().context("invalid input")?.parse::<u64>()?;
-            let src = values.next().context("invalid input")?.parse::<u64>()?;
-            let len = values.next().context("invalid input")?.parse::<u64>()?;
+            let map = current_map.as_mut().ok_or_else(|| {
+                at_line(ParseError::new(&line, &line, "`seeds:` or a map header"))
+            })?;
+
+            let mut range = || {
+                let dst = scanner.integer("a destination range start")?;
+                let src = scanner.integer("a source range start")?;
+                let len = scanner.integer("a range length")?;
+                scanner.end()?;
+
+                Ok((dst, src, len))
+            };
+
+            let (dst, src, len) = range().map_err(at_line)?;
 
-            match current_map.as_mut() {
-                Some(map) => map.add_range(dst, src, len),
-                None => return Err(anyhow!("invalid input")),
+            let (Some(src), Some(_)) = (interval(src, len), interval(dst, len)) else {
+                let e = ParseError::new(&line, &line, format!("a range within {} bits", Int::BITS));
+                return Err(at_line(e).into());
             };
+
+            map.add_range(dst, src);
         }
 
         match current_map.take() {
             Some(map) => maps.push(map),
-            None => return Err(anyhow!("invalid input")),
+            None => return Err(ParseError::missing_line(n_lines + 1, "a map").into()),
         };
 
         Ok(Self {
@@ -215,4 +263,301 @@ impl Almanac {
             maps,
         })
     }
+
+    /// Convert all seeds down to locations and return the minimum of those locations.
+    fn part_one(&self) -> Result<Int> {
+        self.seeds
+            .iter()
+            .map(|seed| self.location(*seed))
+            .min()
+            .context("no seeds")
+    }
+
+    /// Convert the seed ranges down to location ranges, map by map, and return the lowest
+    /// location.
+    fn part_two(&self) -> Result<Int> {
+        self.maps
+            .iter()
+            .try_fold(self.seed_ranges.clone(), |set, map| map.convert_set(&set))?
+            .min()
+            .context("no seeds")
+    }
+}
+
+/// The `len` numbers starting at `start`, if they all fit in an [`Int`].
+fn interval(start: Int, len: Int) -> Option<Interval<Int>> {
+    start.checked_add(len).map(|end| Interval::new(start, end))
+}
+
+pub const KNOBS: &[Knob] = &[
+    Knob::new("seeds", 10, "number of seed ranges"),
+    Knob::new("ranges", 30, "number of ranges in each map"),
+];
+
+/// The categories converted between, in order.
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+/// Generate an almanac.
+///
+/// Like in puzzle inputs, each map shuffles the ranges that make up a block of 32-bit numbers.
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> Result<String> {
+    const LIMIT: u64 = 1 << 32;
+
+    let seeds = knobs.get("seeds");
+    let ranges = knobs.get("ranges");
+
+    ensure!(
+        ranges <= LIMIT,
+        "maps can't have more ranges than 32-bit numbers"
+    );
+
+    let mut almanac = String::from("seeds:");
+
+    for _ in 0..seeds {
+        let start = rng.below(LIMIT);
+        let len = rng.range_inclusive(1..=LIMIT / 10);
+        almanac += &format!(" {start} {len}");
+    }
+
+    almanac.push('\n');
+
+    for names in CATEGORIES.windows(2) {
+        almanac += &format!("\n{}-to-{} map:\n", names[0], names[1]);
+
+        let max_len = LIMIT / ranges.max(1);
+        let lens = (0..ranges)
+            .map(|_| rng.range_inclusive(1..=max_len))
+            .collect::<Vec<_>>();
+
+        let total = lens.iter().sum::<u64>();
+        let start = rng.range_inclusive(0..=LIMIT - total);
+
+        let mut order = (0..lens.len()).collect::<Vec<_>>();
+        rng.shuffle(&mut order);
+
+        let mut lines = Vec::new();
+        let mut src = start;
+
+        for (idx, &len) in lens.iter().enumerate() {
+            let dst = start + order[..idx].iter().map(|&i| lens[i]).sum::<u64>();
+            lines.push(format!("{dst} {src} {len}\n"));
+            src += len;
+        }
+
+        rng.shuffle(&mut lines);
+        almanac.extend(lines);
+    }
+
+    Ok(almanac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prop;
+    use crate::rng::Rng;
+    use std::io::BufReader;
+
+    static EXAMPLE: &str = include_str!("../inputs/day05/examples/example");
+
+    fn parse(s: &str) -> Result<Almanac> {
+        Almanac::parse(BufReader::new(s.as_bytes()))
+    }
+
+    #[test]
+    fn example_input() {
+        let almanac = parse(EXAMPLE).unwrap();
+
+        assert_eq!(almanac.part_one().unwrap(), 35);
+        assert_eq!(almanac.part_two().unwrap(), 46);
+
+        // the old approach to part two: convert back up from the lowest location
+        assert!(almanac.seed_ranges.contains(almanac.seed(46)));
+        assert!(!almanac.seed_ranges.contains(almanac.seed(45)));
+    }
+
+    #[test]
+    fn overlapping_ranges() {
+        let almanac = parse("seeds: 10 1\n\nseed-to-soil map:\n100 10 1\n0 10 1\n").unwrap();
+
+        // the first range wins, whether seeds are converted one by one or as a set
+        assert_eq!(almanac.location(10), 100);
+        assert_eq!(almanac.part_two().unwrap(), 100);
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(parse(EXAMPLE).unwrap().to_string(), EXAMPLE);
+
+        let almanac = "seeds:\n\na-to-b map:\n";
+        assert_eq!(parse(almanac).unwrap().to_string(), almanac);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let e = parse("seeds: 1 2\n\nseed-to-soil map:\n50 98\n").unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.line(), Some(4));
+        assert_eq!(e.expected(), "a range length");
+
+        let e = parse("seeds: 1 2 3\n").unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.line(), Some(1));
+
+        let e = parse("seeds: 1 2\n").unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.to_string(), "line 2: expected a map, found end of input");
+    }
+
+    /// Seed ranges, and maps as lists of `(dst, src, len)` ranges.
+    type Spec = (Vec<(u64, u64)>, Vec<Vec<(u64, u64, u64)>>);
+
+    fn random_seed_ranges(rng: &mut Rng) -> Vec<(u64, u64)> {
+        (0..rng.range_inclusive(1..=3))
+            .map(|_| (rng.range(0..50), rng.range(0..20)))
+            .collect()
+    }
+
+    /// A random almanac whose maps have disjoint source ranges, as in puzzle inputs.
+    fn random_almanac(rng: &mut Rng) -> Spec {
+        let maps = (0..rng.range_inclusive(1..=3))
+            .map(|_| {
+                let mut start = rng.range(0..10);
+
+                (0..rng.range_inclusive(0..=3))
+                    .map(|_| {
+                        let len = rng.range(1..15);
+                        let range = (rng.range(0..60), start, len);
+                        start += len + rng.range(0..10);
+                        range
+                    })
+                    .collect()
+            })
+            .collect();
+
+        (random_seed_ranges(rng), maps)
+    }
+
+    /// A random almanac whose maps only shuffle the pieces of a block of numbers around, so that
+    /// they can be undone.
+    fn random_reversible_almanac(rng: &mut Rng) -> Spec {
+        let maps = (0..rng.range_inclusive(1..=3))
+            .map(|_| {
+                let start = rng.range(0..30);
+
+                let pieces = (0..rng.range_inclusive(1..=4))
+                    .map(|_| rng.range(1..10))
+                    .collect::<Vec<_>>();
+
+                let mut order = (0..pieces.len()).collect::<Vec<_>>();
+                rng.shuffle(&mut order);
+
+                let mut src = start;
+                let mut ranges = Vec::new();
+
+                for (idx, &len) in pieces.iter().enumerate() {
+                    let dst = start + order[..idx].iter().map(|&i| pieces[i]).sum::<u64>();
+                    ranges.push((dst, src, len));
+                    src += len;
+                }
+
+                ranges
+            })
+            .collect();
+
+        (random_seed_ranges(rng), maps)
+    }
+
+    /// The union of `ranges` of `(start, len)`, if none of them overlap.
+    fn disjoint_union<I>(ranges: I) -> Option<IntervalSet<Int>>
+    where
+        I: IntoIterator<Item = (u64, u64)>,
+    {
+        let mut set = IntervalSet::new();
+        let mut total = 0;
+
+        for (start, len) in ranges {
+            set.insert(Interval::with_len(Int::from(start), Int::from(len)));
+            total += Int::from(len);
+        }
+
+        (set.len() == total).then_some(set)
+    }
+
+    fn almanac_text((seeds, maps): &Spec) -> String {
+        let mut text = String::from("seeds:");
+
+        for (start, len) in seeds {
+            text += &format!(" {start} {len}");
+        }
+
+        for (idx, map) in maps.iter().enumerate() {
+            text += &format!("\n\nm{idx}-to-m{} map:", idx + 1);
+
+            for (dst, src, len) in map {
+                text += &format!("\n{dst} {src} {len}");
+            }
+        }
+
+        text
+    }
+
+    fn all_seeds(almanac: &Almanac) -> impl Iterator<Item = Int> + '_ {
+        almanac
+            .seed_ranges
+            .intervals()
+            .iter()
+            .flat_map(|i| i.start()..i.end())
+    }
+
+    #[test]
+    fn set_conversion_agrees_with_seeds() {
+        prop::check(random_almanac, |spec| {
+            let valid = !spec.1.is_empty()
+                && spec.1.iter().all(|map| {
+                    disjoint_union(map.iter().map(|&(_, src, len)| (src, len))).is_some()
+                });
+
+            if !valid {
+                return true;
+            }
+
+            let almanac = parse(&almanac_text(spec)).unwrap();
+            let lowest = all_seeds(&almanac).map(|seed| almanac.location(seed)).min();
+
+            almanac.part_two().ok() == lowest
+        });
+    }
+
+    #[test]
+    fn conversions_round_trip() {
+        prop::check(random_reversible_almanac, |spec| {
+            let valid = !spec.1.is_empty()
+                && spec.1.iter().all(|map| {
+                    let src = disjoint_union(map.iter().map(|&(_, src, len)| (src, len)));
+                    let dst = disjoint_union(map.iter().map(|&(dst, _, len)| (dst, len)));
+                    src.is_some() && src == dst
+                });
+
+            if !valid {
+                return true;
+            }
+
+            let almanac = parse(&almanac_text(spec)).unwrap();
+
+            let round_trips =
+                all_seeds(&almanac).all(|seed| almanac.seed(almanac.location(seed)) == seed);
+
+            round_trips
+        });
+    }
 }
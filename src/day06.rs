@@ -1,4 +1,11 @@
-use anyhow::{Context, Result};
+use crate::checked::{self, Int};
+use crate::error::{OverflowError, ParseError};
+use crate::generate::{Knob, Knobs};
+use crate::parse::Scanner;
+use crate::rng::Rng;
+use crate::Solution;
+use anyhow::{ensure, Result};
+use std::fmt;
 use std::io::BufRead;
 
 #[derive(Debug, Copy, Clone)]
@@ -12,8 +19,7 @@ impl Race {
         let mut n = 0;
 
         for t in 0..self.time {
-            let d = (self.time - t) * t;
-            if d > self.distance {
+            if self.wins(t) {
                 n += 1;
             }
         }
@@ -21,58 +27,91 @@ impl Race {
         n
     }
 
-    pub fn solve(&self) -> u64 {
+    /// Whether holding the button for `hold` milliseconds beats the record.
+    fn wins(&self, hold: u64) -> bool {
+        let d = (self.time - hold) as u128 * hold as u128;
+        d > self.distance as u128
+    }
+
+    /// Count the winning holds without trying them all.
+    ///
+    /// The distance is `(time - hold) * hold`, so the winning holds lie symmetrically around
+    /// `time / 2`, between the roots of `hold² - time * hold + distance`. The roots are estimated
+    /// with floating point and then corrected, as they may be off for large races.
+    pub fn solve(&self) -> Result<Int, OverflowError> {
+        let mid = self.time / 2;
+
+        if !self.wins(mid) {
+            return Ok(0);
+        }
+
         let time = self.time as f64;
         let distance = self.distance as f64;
 
-        let sqrt = (time.powi(2) - 4.0 * distance).sqrt();
+        let sqrt = (time.powi(2) - 4.0 * distance).max(0.0).sqrt();
+        let mut lower = (((time - sqrt) / 2.0).floor().max(0.0) as u64).min(mid);
+
+        while lower > 0 && self.wins(lower - 1) {
+            lower -= 1;
+        }
 
-        let lower = (-time - sqrt) / 2.0;
-        let upper = (-time + sqrt) / 2.0;
+        while !self.wins(lower) {
+            lower += 1;
+        }
 
-        (upper.ceil() - lower.floor()) as u64 - 1
+        let outside = checked::mul(2, Int::from(lower))?;
+        checked::add(checked::sub(Int::from(self.time), outside)?, 1)
     }
 
-    fn parse_numbers(s: &str) -> Vec<u64> {
-        let mut n = 0;
-        let mut factor = 1;
-        let mut ns = Vec::new();
-
-        for chr in s.chars().rev() {
-            if let Some(digit) = chr.to_digit(10) {
-                n += digit as u64 * factor;
-                factor *= 10;
-            } else if n > 0 {
-                ns.push(n);
-                n = 0;
-                factor = 1;
-            }
+    /// Read the line labelled `label`, e.g. `Time:      7  15   30`.
+    fn read_line<R>(reader: R, line_no: usize, label: &str) -> Result<String>
+    where
+        R: BufRead,
+    {
+        match reader.lines().next() {
+            Some(line) => Ok(line?),
+            None => Err(ParseError::missing_line(line_no, format!("`{label}`")).into()),
         }
+    }
 
-        ns
+    /// The numbers on a line labelled `label`.
+    fn parse_numbers(line: &str, label: &str) -> Result<Vec<u64>, ParseError> {
+        let mut scanner = Scanner::new(line);
+        scanner.tag(label)?;
+        scanner.integers()
     }
 
-    fn parse_number(s: &str) -> u64 {
-        let mut n = 0;
-        let mut factor = 1;
+    /// The single number on a line labelled `label`, ignoring the spaces between its digits.
+    fn parse_number(line: &str, label: &str) -> Result<u64, ParseError> {
+        // make sure there's nothing but numbers first, so errors point at the right place
+        Self::parse_numbers(line, label)?;
 
-        for chr in s.chars().rev() {
-            if let Some(digit) = chr.to_digit(10) {
-                n += digit as u64 * factor;
-                factor *= 10;
-            }
-        }
+        let mut scanner = Scanner::new(line);
+        scanner.tag(label)?;
 
-        n
+        let digits = scanner.rest().trim();
+        let kerned = digits.split_whitespace().collect::<String>();
+
+        kerned
+            .parse()
+            .map_err(|_| ParseError::new(line, digits, "a smaller number"))
     }
 
-    pub fn parse_p1<R>(reader: R) -> Result<Vec<Self>>
+    pub fn parse_p1<R>(mut reader: R) -> Result<Vec<Self>>
     where
         R: BufRead,
     {
-        let mut lines = reader.lines();
-        let times = Self::parse_numbers(&lines.next().context("invalid input")??);
-        let distances = Self::parse_numbers(&lines.next().context("invalid input")??);
+        let time_line = Self::read_line(&mut reader, 1, "Time:")?;
+        let distance_line = Self::read_line(&mut reader, 2, "Distance:")?;
+
+        let times = Self::parse_numbers(&time_line, "Time:").map_err(|e| e.with_line(1))?;
+        let distances =
+            Self::parse_numbers(&distance_line, "Distance:").map_err(|e| e.with_line(2))?;
+
+        if times.len() != distances.len() {
+            let e = ParseError::new(&distance_line, &distance_line, "one distance per time");
+            return Err(e.with_line(2).into());
+        }
 
         let races = times
             .into_iter()
@@ -83,41 +122,244 @@ impl Race {
         Ok(races)
     }
 
-    pub fn parse_p2<R>(reader: R) -> Result<Self>
+    pub fn parse_p2<R>(mut reader: R) -> Result<Self>
     where
         R: BufRead,
     {
-        let mut lines = reader.lines();
-        let time = Self::parse_number(&lines.next().context("invalid input")??);
-        let distance = Self::parse_number(&lines.next().context("invalid input")??);
+        let time_line = Self::read_line(&mut reader, 1, "Time:")?;
+        let distance_line = Self::read_line(&mut reader, 2, "Distance:")?;
+
+        let time = Self::parse_number(&time_line, "Time:").map_err(|e| e.with_line(1))?;
+        let distance =
+            Self::parse_number(&distance_line, "Distance:").map_err(|e| e.with_line(2))?;
 
         Ok(Self { time, distance })
     }
 }
 
+/// The sheet of paper listing the races.
+///
+/// Part one reads it as several races, part two as a single race with very bad kerning.
+#[derive(Debug)]
+pub struct Races {
+    races: Vec<Race>,
+    kerned: Race,
+}
+
+/// Split `digits` into one number per value in `values`, each with any leading zeros it needs so
+/// that they run together to `digits` again.
+fn split_digits(digits: &str, values: &[u64]) -> Option<Vec<String>> {
+    let Some((last, rest)) = values.split_last() else {
+        return digits.is_empty().then(Vec::new);
+    };
+
+    let mut start = digits.strip_suffix(&last.to_string())?.len();
+
+    // try the fewest leading zeros first, and give the rest to the numbers before
+    loop {
+        if let Some(mut numbers) = split_digits(&digits[..start], rest) {
+            numbers.push(digits[start..].to_owned());
+            return Some(numbers);
+        }
+
+        if !digits[..start].ends_with('0') {
+            return None;
+        }
+
+        start -= 1;
+    }
+}
+
+/// The numbers of one line of the sheet, as they were written: with the leading zeros that make
+/// them read as `kerned` in part two.
+fn kern(values: &[u64], kerned: u64) -> Vec<String> {
+    // leading zeros of the first numbers don't show in `kerned`
+    let len = values.iter().map(|value| value.to_string().len()).sum();
+    let digits = format!("{kerned:0len$}");
+
+    split_digits(&digits, values).unwrap_or_else(|| values.iter().map(u64::to_string).collect())
+}
+
+/// Writes the sheet with its numbers in columns, like the puzzle input.
+impl fmt::Display for Races {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let times = self.races.iter().map(|race| race.time).collect::<Vec<_>>();
+        let distances = self
+            .races
+            .iter()
+            .map(|race| race.distance)
+            .collect::<Vec<_>>();
+
+        let times = kern(&times, self.kerned.time);
+        let distances = kern(&distances, self.kerned.distance);
+
+        let widths = times
+            .iter()
+            .zip(&distances)
+            .map(|(time, distance)| time.len().max(distance.len()) + 2)
+            .collect::<Vec<_>>();
+
+        for (label, numbers) in [("Time:", times), ("Distance:", distances)] {
+            write!(f, "{label:<9}")?;
+
+            for (number, width) in numbers.iter().zip(&widths) {
+                write!(f, "{number:>width$}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Solution for Races {
+    type PartOne = Int;
+    type PartTwo = Int;
+
+    fn parse<R>(mut reader: R) -> Result<Self>
+    where
+        R: BufRead,
+    {
+        let mut sheet = String::new();
+        reader.read_to_string(&mut sheet)?;
+
+        let races = Race::parse_p1(sheet.as_bytes())?;
+        let kerned = Race::parse_p2(sheet.as_bytes())?;
+
+        Ok(Self { races, kerned })
+    }
+
+    fn part_one(&self) -> Result<Int> {
+        Ok(checked::product(
+            self.races.iter().map(Race::n_winning_holds),
+        )?)
+    }
+
+    fn part_two(&self) -> Result<Int> {
+        Ok(self.kerned.solve()?)
+    }
+}
+
+pub const KNOBS: &[Knob] = &[Knob::new("races", 4, "number of races, at most 4")];
+
+/// Generate a sheet of races, all of which can be won.
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> Result<String> {
+    let n = knobs.get("races");
+
+    // part two reads all races as one, with up to 4 digits of distance per race
+    ensure!(
+        (1..=4).contains(&n),
+        "the kerned race only fits in 64 bits for 1 to 4 races"
+    );
+
+    let mut times = String::from("Time:    ");
+    let mut distances = String::from("Distance:");
+
+    for _ in 0..n {
+        let time = rng.range_inclusive(7..=99);
+        let record = (time / 2) * (time - time / 2);
+        let distance = rng.below(record);
+
+        let width = distance.to_string().len() + 2;
+        times += &format!("{time:>width$}");
+        distances += &format!("{distance:>width$}");
+    }
+
+    Ok(format!("{times}\n{distances}\n"))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Race;
+    use super::{Race, Races};
+    use crate::checked::Int;
+    use crate::error::ParseError;
+    use crate::prop;
+    use crate::Solution;
     use std::io::BufReader;
 
-    const EXAMPLE: &str = "Time:      7  15   30\nDistance:  9  40  200";
+    static EXAMPLE: &str = include_str!("../inputs/day06/examples/example");
 
     #[test]
     fn test_examples() {
         // part one
         let races = Race::parse_p1(BufReader::new(EXAMPLE.as_bytes())).unwrap();
         let naive_result = races.iter().map(Race::n_winning_holds).product::<u64>();
-        let smart_result = races.iter().map(Race::solve).product();
+        let smart_result = races
+            .iter()
+            .map(|race| race.solve().unwrap())
+            .product::<Int>();
 
         assert_eq!(naive_result, 288);
-        assert_eq!(naive_result, smart_result);
+        assert_eq!(Int::from(naive_result), smart_result);
 
         // part two
         let race = Race::parse_p2(BufReader::new(EXAMPLE.as_bytes())).unwrap();
         let naive_result = race.n_winning_holds();
-        let smart_result = race.solve();
+        let smart_result = race.solve().unwrap();
 
         assert_eq!(naive_result, 71503);
-        assert_eq!(naive_result, smart_result);
+        assert_eq!(Int::from(naive_result), smart_result);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let e = Race::parse_p1(BufReader::new("Time: 7 15\nDistanc: 9 40".as_bytes())).unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.line(), Some(2));
+        assert_eq!(e.found(), "Distanc");
+
+        let e = Race::parse_p2(BufReader::new("Time: 7 15\n".as_bytes())).unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 2: expected `Distance:`, found end of input"
+        );
+
+        let e =
+            Race::parse_p1(BufReader::new("Time: 7 1x5\nDistance: 9 40".as_bytes())).unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.found(), "1x5");
+    }
+
+    #[test]
+    fn zeros() {
+        let sheet = "Time: 30 10\nDistance: 200 0";
+
+        let races = Race::parse_p1(BufReader::new(sheet.as_bytes())).unwrap();
+        assert_eq!(races.len(), 2);
+        assert_eq!(races[1].n_winning_holds(), 9);
+
+        let race = Race::parse_p2(BufReader::new(sheet.as_bytes())).unwrap();
+        assert_eq!((race.time, race.distance), (3010, 2000));
+    }
+
+    #[test]
+    fn round_trip() {
+        let races = Races::parse(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(races.to_string(), EXAMPLE);
+
+        // leading zeros change the kerned race, so they are kept
+        let sheet = "Time: 7 05 00\nDistance: 009 0 30\n";
+        let races = Races::parse(sheet.as_bytes()).unwrap();
+        assert_eq!((races.kerned.time, races.kerned.distance), (70500, 9030));
+
+        let text = races.to_string();
+        assert_eq!(text, "Time:      7  05  00\nDistance:  9   0  30\n");
+
+        let parsed = Races::parse(text.as_bytes()).unwrap();
+        assert_eq!((parsed.kerned.time, parsed.kerned.distance), (70500, 9030));
+        assert_eq!(parsed.to_string(), text);
+    }
+
+    #[test]
+    fn solve_agrees_with_counting() {
+        prop::check(
+            |rng| (rng.range(0..200), rng.range(0..12_000)),
+            |&(time, distance)| {
+                let race = Race { time, distance };
+                race.solve() == Ok(Int::from(race.n_winning_holds()))
+            },
+        );
     }
 }
@@ -1,6 +1,14 @@
-use anyhow::{anyhow, Context};
+use crate::checked::{self, Int};
+use crate::error::{OverflowError, ParseError};
+use crate::generate::{Knob, Knobs};
+use crate::parse::Scanner;
+use crate::rng::Rng;
+use crate::Solution;
+use anyhow::ensure;
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::convert::TryFrom;
+use std::fmt;
 use std::io::BufRead;
 use std::str::FromStr;
 
@@ -37,9 +45,11 @@ impl<const JOKERS: bool> Ord for Card<JOKERS> {
 }
 
 impl<const JOKERS: bool> TryFrom<char> for Card<JOKERS> {
-    type Error = anyhow::Error;
+    type Error = ParseError;
 
     fn try_from(c: char) -> Result<Self, Self::Error> {
+        let invalid = || ParseError::unexpected(c, "a card");
+
         match c {
             'A' => Ok(Self(14)),
             'K' => Ok(Self(13)),
@@ -49,22 +59,38 @@ impl<const JOKERS: bool> TryFrom<char> for Card<JOKERS> {
             _ => {
                 if let Some(digit) = c.to_digit(10) {
                     if !(2..=9).contains(&digit) {
-                        Err(anyhow!("not a valid card: {c}"))
+                        Err(invalid())
                     } else {
                         Ok(Self(digit))
                     }
                 } else {
-                    Err(anyhow!("not a valid card: {c}"))
+                    Err(invalid())
                 }
             }
         }
     }
 }
 
+/// Writes the card as its label, e.g. `T` for a ten.
+impl<const JOKERS: bool> fmt::Display for Card<JOKERS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.0 {
+            14 => 'A',
+            13 => 'K',
+            12 => 'Q',
+            11 => 'J',
+            10 => 'T',
+            n => char::from_digit(n, 10).ok_or(fmt::Error)?,
+        };
+
+        write!(f, "{label}")
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Hand<const JOKERS: bool> {
     cards: [Card<JOKERS>; 5],
-    bet: u32,
+    bet: u64,
 }
 
 impl<const JOKERS: bool> Hand<JOKERS> {
@@ -99,8 +125,18 @@ impl<const JOKERS: bool> Hand<JOKERS> {
         ((most + jokers).min(5), second_most + rem)
     }
 
-    pub fn value(&self, rank: usize) -> u32 {
-        self.bet * (rank as u32)
+    pub fn value(&self, rank: usize) -> Result<Int, OverflowError> {
+        checked::mul(Int::from(self.bet), checked::convert(rank)?)
+    }
+
+    /// Total winnings of a set of hands, which must be sorted by rank.
+    pub fn winnings(hands: &[Self]) -> Result<Int, OverflowError> {
+        checked::try_sum(
+            hands
+                .iter()
+                .enumerate()
+                .map(|(idx, hand)| hand.value(idx + 1)),
+        )
     }
 
     pub fn parse<R>(reader: R) -> anyhow::Result<Vec<Self>>
@@ -109,10 +145,11 @@ impl<const JOKERS: bool> Hand<JOKERS> {
     {
         let mut hands = Vec::new();
 
-        for line in reader.lines() {
-            let line = line?;
-
-            hands.push(line.parse()?);
+        for (idx, line) in reader.lines().enumerate() {
+            let hand = line?
+                .parse()
+                .map_err(|e: ParseError| e.with_line(idx + 1))?;
+            hands.push(hand);
         }
 
         hands.sort();
@@ -155,47 +192,142 @@ impl<const JOKERS: bool> Ord for Hand<JOKERS> {
 }
 
 impl<const JOKERS: bool> FromStr for Hand<JOKERS> {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
+    /// Parse a hand like `32T3K 765`.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut hand = Self::default();
 
-        let mut components = s.split(' ');
+        let mut scanner = Scanner::new(s);
+        let cards = scanner.word("five cards")?;
+
+        if cards.chars().count() != hand.cards.len() {
+            return Err(ParseError::new(s, cards, "five cards"));
+        }
 
-        for (i, c) in components
-            .next()
-            .context("invalid hand")?
-            .chars()
-            .enumerate()
-        {
-            hand.cards[i] = Card::try_from(c)?;
+        for (i, (idx, c)) in cards.char_indices().enumerate() {
+            let card = &cards[idx..idx + c.len_utf8()];
+            hand.cards[i] =
+                Card::try_from(c).map_err(|e| ParseError::new(s, card, e.expected()))?;
         }
 
-        hand.bet = components.next().context("invalid hand")?.parse()?;
+        hand.bet = scanner.integer("a bet")?;
+        scanner.end()?;
 
         Ok(hand)
     }
 }
 
+/// Writes a hand like `32T3K 765`.
+impl<const JOKERS: bool> fmt::Display for Hand<JOKERS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for card in self.cards.iter() {
+            write!(f, "{card}")?;
+        }
+
+        write!(f, " {}", self.bet)
+    }
+}
+
+/// The list of hands, ranked with and without jokers.
+#[derive(Debug)]
+pub struct Hands {
+    plain: Vec<Hand<false>>,
+    jokers: Vec<Hand<true>>,
+}
+
+/// Writes the hands ranked without jokers, which ranks them the same way with jokers again: the
+/// sort is stable, and hands only tie if they have the same cards.
+impl fmt::Display for Hands {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for hand in self.plain.iter() {
+            writeln!(f, "{hand}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Solution for Hands {
+    type PartOne = Int;
+    type PartTwo = Int;
+
+    fn parse<R>(mut reader: R) -> anyhow::Result<Self>
+    where
+        R: BufRead,
+    {
+        let mut list = String::new();
+        reader.read_to_string(&mut list)?;
+
+        let plain = Hand::parse(list.as_bytes())?;
+        let jokers = Hand::parse(list.as_bytes())?;
+
+        Ok(Self { plain, jokers })
+    }
+
+    fn part_one(&self) -> anyhow::Result<Int> {
+        Ok(Hand::winnings(&self.plain)?)
+    }
+
+    fn part_two(&self) -> anyhow::Result<Int> {
+        Ok(Hand::winnings(&self.jokers)?)
+    }
+}
+
+pub const KNOBS: &[Knob] = &[
+    Knob::new("hands", 1000, "number of hands"),
+    Knob::new("bid", 1000, "highest bid"),
+];
+
+/// Generate a list of distinct hands and their bids.
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> anyhow::Result<String> {
+    const CARDS: [char; 13] = [
+        '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+    ];
+
+    let hands = knobs.get("hands");
+    let bid = knobs.get("bid");
+
+    ensure!(bid > 0, "bids must be positive");
+    ensure!(hands <= 13u64.pow(5), "there are only 13^5 different hands");
+
+    let fits = (Int::from(hands) * Int::from(hands + 1) / 2)
+        .checked_mul(Int::from(bid))
+        .is_some();
+    ensure!(
+        fits,
+        "too many hands or too high bids for the answers to fit in {} bits",
+        Int::BITS
+    );
+
+    let mut seen = HashSet::new();
+    let mut list = String::new();
+
+    while (seen.len() as u64) < hands {
+        let hand = (0..5).map(|_| *rng.choose(&CARDS)).collect::<String>();
+
+        // equal hands would rank in any order, making the answers ambiguous
+        if seen.insert(hand.clone()) {
+            list += &format!("{hand} {}\n", rng.range_inclusive(1..=bid));
+        }
+    }
+
+    Ok(list)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::BufReader;
 
-    static EXAMPLE: &str = "32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483";
+    static EXAMPLE: &str = include_str!("../inputs/day07/examples/example");
 
     #[test]
     fn example_input_part1() {
         let reader = BufReader::new(EXAMPLE.as_bytes());
         let hands = Hand::<false>::parse(reader).unwrap();
 
-        let winnings: u32 = hands
-            .iter()
-            .enumerate()
-            .map(|(idx, hand)| hand.value(idx + 1))
-            .sum();
-
-        assert_eq!(winnings, 6440);
+        assert_eq!(Hand::winnings(&hands).unwrap(), 6440);
     }
 
     #[test]
@@ -203,12 +335,40 @@ mod tests {
         let reader = BufReader::new(EXAMPLE.as_bytes());
         let hands = Hand::<true>::parse(reader).unwrap();
 
-        let winnings: u32 = hands
-            .iter()
-            .enumerate()
-            .map(|(idx, hand)| hand.value(idx + 1))
-            .sum();
+        assert_eq!(Hand::winnings(&hands).unwrap(), 5905);
+    }
+
+    #[test]
+    fn round_trip() {
+        let hands = Hands::parse(EXAMPLE.as_bytes()).unwrap();
+        let text = hands.to_string();
+        assert_eq!(
+            text,
+            "32T3K 765\nKTJJT 220\nKK677 28\nT55J5 684\nQQQJA 483\n"
+        );
+
+        let parsed = Hands::parse(text.as_bytes()).unwrap();
+        assert_eq!(parsed.to_string(), text);
+
+        let ranked = |hands: &Hands| hands.jokers.iter().map(Hand::to_string).collect::<Vec<_>>();
+        assert_eq!(ranked(&parsed), ranked(&hands));
+    }
+
+    #[test]
+    fn invalid_hands() {
+        let e = "32T1K 765".parse::<Hand<false>>().unwrap_err();
+        assert_eq!(e.column(), 4);
+        assert_eq!(e.expected(), "a card");
+
+        let e = "32T3 765".parse::<Hand<false>>().unwrap_err();
+        assert_eq!(e.found(), "32T3");
+
+        let e = "32T3K".parse::<Hand<false>>().unwrap_err();
+        assert_eq!(e.expected(), "a bet");
 
-        assert_eq!(winnings, 5905);
+        let e = Hand::<true>::parse(BufReader::new("32T3K 765\nKK677 x".as_bytes())).unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.line(), Some(2));
+        assert_eq!(e.column(), 7);
     }
 }
@@ -1,5 +1,13 @@
-use anyhow::anyhow;
+use crate::checked::{self, Int};
+use crate::error::ParseError;
+use crate::generate::{Knob, Knobs};
+use crate::parse::Scanner;
+use crate::rng::Rng;
+use crate::Solution;
+use anyhow::{bail, ensure, Context};
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
 use std::io::BufRead;
 use std::str::FromStr;
 
@@ -9,9 +17,15 @@ enum Instruction {
     Right,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct NodeId(char, char, char);
 
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.0, self.1, self.2)
+    }
+}
+
 impl NodeId {
     fn is_start(self) -> bool {
         self.2 == 'A'
@@ -23,23 +37,19 @@ impl NodeId {
 }
 
 impl FromStr for NodeId {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 3 {
-            Err(anyhow!("unexpected input!"))
-        } else {
-            let mut chars = s.chars();
-            Ok(Self(
-                chars.next().unwrap(),
-                chars.next().unwrap(),
-                chars.next().unwrap(),
-            ))
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next(), chars.next(), chars.next()) {
+            (Some(a), Some(b), Some(c), None) => Ok(Self(a, b, c)),
+            _ => Err(ParseError::new(s, s, "a node ID of three characters")),
         }
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 struct Node {
     left: NodeId,
     right: NodeId,
@@ -53,40 +63,110 @@ impl Node {
         }
     }
 
-    fn parse(s: &str) -> anyhow::Result<(NodeId, Self)> {
+    fn parse(s: &str) -> Result<(NodeId, Self), ParseError> {
         // input should be of the form "ABC = (DEF, GHI)"
 
-        let node_id = s[..3].parse()?;
-        let left = s[7..10].parse()?;
-        let right = s[12..15].parse()?;
+        let node_id = |id: &str| {
+            id.parse::<NodeId>()
+                .map_err(|e| ParseError::new(s, id, e.expected()))
+        };
+
+        let mut scanner = Scanner::new(s);
 
-        Ok((node_id, Node { left, right }))
+        let id = scanner.word("a node ID")?;
+        scanner.tag("=")?;
+        scanner.tag("(")?;
+        let left = scanner.word("a node ID")?;
+        scanner.tag(",")?;
+        let right = scanner.word("a node ID")?;
+        scanner.tag(")")?;
+        scanner.end()?;
+
+        let node = Node {
+            left: node_id(left)?,
+            right: node_id(right)?,
+        };
+
+        Ok((node_id(id)?, node))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Map {
     instructions: Vec<Instruction>,
     nodes: HashMap<NodeId, Node>,
 }
 
+/// Writes the instructions, then the nodes in order of their IDs.
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for instruction in self.instructions.iter() {
+            let c = match instruction {
+                Instruction::Left => 'L',
+                Instruction::Right => 'R',
+            };
+
+            write!(f, "{c}")?;
+        }
+
+        writeln!(f)?;
+        writeln!(f)?;
+
+        let mut ids = self.nodes.keys().collect::<Vec<_>>();
+        ids.sort_unstable();
+
+        for id in ids {
+            let node = self.nodes[id];
+            writeln!(f, "{id} = ({}, {})", node.left, node.right)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Map {
-    pub fn solve_p1(&self) -> u32 {
-        let mut current = NodeId::from_str("AAA").unwrap();
-        let goal = NodeId::from_str("ZZZ").unwrap();
+    fn node(&self, id: NodeId) -> anyhow::Result<Node> {
+        self.nodes
+            .get(&id)
+            .copied()
+            .with_context(|| format!("no node {id} in the map"))
+    }
+
+    /// The instructions, repeated forever.
+    fn steps(&self) -> anyhow::Result<impl Iterator<Item = Instruction> + '_> {
+        ensure!(!self.instructions.is_empty(), "the map has no instructions");
+        Ok(self.instructions.iter().copied().cycle())
+    }
+
+    /// The number of steps after which a walk is sure to be going round in circles: by then, it
+    /// has been at some node at the same point in the instructions twice.
+    fn walk_limit(&self) -> Int {
+        (self.nodes.len() * self.instructions.len()) as Int
+    }
+
+    pub fn solve_p1(&self) -> anyhow::Result<Int> {
+        let mut current = NodeId('A', 'A', 'A');
+        let goal = NodeId('Z', 'Z', 'Z');
+
+        let instructions = self.steps()?;
+        self.node(goal)?;
 
         let mut steps = 0;
 
-        for instruction in self.instructions.iter().cycle() {
-            current = self.nodes[&current].next(*instruction);
+        for instruction in instructions {
+            current = self.node(current)?.next(instruction);
             steps += 1;
 
             if current == goal {
-                return steps;
+                return Ok(steps);
+            }
+
+            if steps > self.walk_limit() {
+                bail!("{goal} can't be reached from AAA");
             }
         }
 
-        unreachable!()
+        unreachable!("the instructions repeat forever")
     }
 
     /// Calculate the offset of the first end-candidate node in a cycle.
@@ -96,7 +176,8 @@ impl Map {
     ///
     /// This assumes that there is an end node (node ID ends with 'Z') somewhere on this cycle for
     /// the root node. Given the puzzle input, this assumption turned out to be true. If this
-    /// assumption is false for some input, this function will loop endlessly.
+    /// assumption is false for some input, this function fails once the walk must have gone round
+    /// the whole cycle.
     ///
     /// This also assumes that the entire path is part of the cycle.
     ///
@@ -104,19 +185,23 @@ impl Map {
     /// the offset along the path until the cycle starts, and the offsets from cycle-start for all
     /// end nodes. But that would make this whole program a good amount more complicated. The way
     /// this is is sufficient for solving the puzzle.
-    fn candidate_offset(&self, root: NodeId) -> u32 {
+    fn candidate_offset(&self, root: NodeId) -> anyhow::Result<Int> {
         let mut current = root;
         let mut found = HashMap::new();
 
         let mut steps = 0;
 
-        for instruction in self.instructions.iter().cycle() {
-            current = self.nodes[&current].next(*instruction);
+        for instruction in self.steps()? {
+            current = self.node(current)?.next(instruction);
             steps += 1;
 
+            if steps > 2 * self.walk_limit() {
+                bail!("no node ending in Z comes round again from {root}");
+            }
+
             if current.is_end() {
                 if let Some(offset) = found.get(&current).copied() {
-                    return offset;
+                    return Ok(offset);
                     // HACK: assume only one possible candidate
                     // found.retain(|_, node_offset| *node_offset >= offset);
                     // return found;
@@ -126,10 +211,10 @@ impl Map {
             }
         }
 
-        unreachable!()
+        unreachable!("the instructions repeat forever")
     }
 
-    pub fn solve_p2(&self) -> u64 {
+    pub fn solve_p2(&self) -> anyhow::Result<Int> {
         // For each start node, calculate the offset until an end-node is found.
         // Then, calculate the lowest common multiple of all these offsets. The assumption here is
         // that all paths are cycles; thus the solution is the LCM of these offsets.
@@ -140,29 +225,34 @@ impl Map {
         // cycle begins. Accounting for this would make the solution more complex, though, and I
         // can't be bothered right now.
 
+        ensure!(
+            self.nodes.keys().any(|n| n.is_start()),
+            "no node ending in A to start from"
+        );
+
         let mut lcm = 1;
 
-        for offset in self
-            .nodes
-            .keys()
-            .filter(|n| n.is_start())
-            .map(|n| self.candidate_offset(*n))
-            .map(|n| n as u64)
-        {
-            lcm = num::integer::lcm(offset, lcm);
+        for start in self.nodes.keys().filter(|n| n.is_start()) {
+            let offset = self.candidate_offset(*start)?;
+            lcm = checked::mul(lcm / num::integer::gcd(lcm, offset), offset)?;
         }
 
-        lcm
+        Ok(lcm)
     }
+}
+
+impl Solution for Map {
+    type PartOne = Int;
+    type PartTwo = Int;
 
-    pub fn parse<R>(reader: R) -> anyhow::Result<Self>
+    fn parse<R>(reader: R) -> anyhow::Result<Self>
     where
         R: BufRead,
     {
         let mut instructions = Vec::new();
         let mut nodes = HashMap::new();
 
-        for line in reader.lines() {
+        for (idx, line) in reader.lines().enumerate() {
             let line = line?;
 
             if line.is_empty() {
@@ -170,18 +260,21 @@ impl Map {
             }
 
             if instructions.is_empty() {
-                for c in line.chars() {
+                for (col, c) in line.char_indices() {
                     match c {
                         'L' => instructions.push(Instruction::Left),
                         'R' => instructions.push(Instruction::Right),
-                        _ => return Err(anyhow!("unexpected input")),
+                        _ => {
+                            let e = ParseError::at(&line, col, col + c.len_utf8(), "`L` or `R`");
+                            return Err(e.with_line(idx + 1).into());
+                        }
                     }
                 }
 
                 continue;
             }
 
-            let (id, node) = Node::parse(&line)?;
+            let (id, node) = Node::parse(&line).map_err(|e| e.with_line(idx + 1))?;
             nodes.insert(id, node);
         }
 
@@ -190,6 +283,122 @@ impl Map {
             nodes,
         })
     }
+
+    fn part_one(&self) -> anyhow::Result<Int> {
+        self.solve_p1()
+    }
+
+    fn part_two(&self) -> anyhow::Result<Int> {
+        self.solve_p2()
+    }
+}
+
+pub const KNOBS: &[Knob] = &[
+    Knob::new("instructions", 61, "number of instructions"),
+    Knob::new("ghosts", 4, "number of starting nodes, at most 8"),
+];
+
+/// Cycle lengths of the ghosts, in multiples of the number of instructions.
+const CYCLES: [u64; 8] = [3, 5, 7, 11, 13, 17, 19, 23];
+
+/// A random node ID ending in `last`, that isn't in `taken` yet.
+fn random_node_id(rng: &mut Rng, taken: &mut HashSet<String>, letters: &[u8]) -> String {
+    loop {
+        let id = [
+            b'A' + rng.below(26) as u8,
+            b'A' + rng.below(26) as u8,
+            *rng.choose(letters),
+        ];
+
+        let id = String::from_utf8_lossy(&id).into_owned();
+
+        if taken.insert(id.clone()) {
+            return id;
+        }
+    }
+}
+
+/// Generate a map with the structure that [`Map::solve_p2`] relies on.
+///
+/// Each ghost starts at a node ending in `A`, and reaches its only node ending in `Z` after a
+/// whole number of passes over the instructions. From there it follows the same cycle again. The
+/// first ghost goes from `AAA` to `ZZZ`, for part one.
+///
+/// Nodes have one side on the path of their ghost, and the other pointing anywhere on it.
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> anyhow::Result<String> {
+    let len = knobs.get("instructions");
+    let ghosts = knobs.get("ghosts") as usize;
+
+    ensure!(len > 0, "there must be at least one instruction");
+    ensure!(
+        (1..=CYCLES.len()).contains(&ghosts),
+        "there can be 1 to {} ghosts",
+        CYCLES.len()
+    );
+
+    let mut cycles = CYCLES;
+    rng.shuffle(&mut cycles);
+    let cycles = &cycles[..ghosts];
+
+    // 26 * 26 * 24 IDs don't end in `A` or `Z`
+    let nodes = cycles.iter().map(|c| c * len).sum::<u64>();
+    ensure!(nodes <= 15_000, "too many nodes to give them all IDs");
+
+    let instructions = (0..len)
+        .map(|_| *rng.choose(&[Instruction::Left, Instruction::Right]))
+        .collect::<Vec<_>>();
+
+    let mut map = instructions
+        .iter()
+        .map(|i| match i {
+            Instruction::Left => 'L',
+            Instruction::Right => 'R',
+        })
+        .collect::<String>();
+    map += "\n\n";
+
+    let inner = (b'B'..b'Z').collect::<Vec<_>>();
+    let mut taken = HashSet::from(["AAA".to_owned(), "ZZZ".to_owned()]);
+    let mut lines = Vec::new();
+
+    for (ghost, &cycle) in cycles.iter().enumerate() {
+        let steps = (cycle * len) as usize;
+
+        let (start, end) = if ghost == 0 {
+            ("AAA".to_owned(), "ZZZ".to_owned())
+        } else {
+            (
+                random_node_id(rng, &mut taken, b"A"),
+                random_node_id(rng, &mut taken, b"Z"),
+            )
+        };
+
+        let mut path = vec![start];
+
+        for _ in 1..steps {
+            path.push(random_node_id(rng, &mut taken, &inner));
+        }
+
+        path.push(end);
+
+        for (step, id) in path.iter().enumerate() {
+            // the start and end node are both followed by the second node
+            let next = &path[if step == steps { 1 } else { step + 1 }];
+            let other = &path[rng.range_inclusive(1..=steps as u64) as usize];
+
+            let (left, right) = match instructions[step % len as usize] {
+                Instruction::Left => (next, other),
+                Instruction::Right => (other, next),
+            };
+
+            lines.push(format!("{id} = ({left}, {right})\n"));
+        }
+    }
+
+    rng.shuffle(&mut lines);
+    map.extend(lines);
+
+    Ok(map)
 }
 
 #[cfg(test)]
@@ -197,45 +406,141 @@ mod tests {
     use super::*;
     use std::io::BufReader;
 
-    static EXAMPLE_A: &str = "RL
-
-AAA = (BBB, CCC)
-BBB = (DDD, EEE)
-CCC = (ZZZ, GGG)
-DDD = (DDD, DDD)
-EEE = (EEE, EEE)
-GGG = (GGG, GGG)
-ZZZ = (ZZZ, ZZZ)";
+    static EXAMPLE_A: &str = include_str!("../inputs/day08/examples/two-steps");
 
-    static EXAMPLE_B: &str = "LLR
+    static EXAMPLE_B: &str = include_str!("../inputs/day08/examples/six-steps");
 
-AAA = (BBB, BBB)
-BBB = (AAA, ZZZ)
-ZZZ = (ZZZ, ZZZ)";
-
-    static EXAMPLE_P2: &str = "LR
-
-11A = (11B, XXX)
-11B = (XXX, 11Z)
-11Z = (11B, XXX)
-22A = (22B, XXX)
-22B = (22C, 22C)
-22C = (22Z, 22Z)
-22Z = (22B, 22B)
-XXX = (XXX, XXX)";
+    static EXAMPLE_P2: &str = include_str!("../inputs/day08/examples/ghosts");
 
     #[test]
     fn example_input_part1() {
         let map = Map::parse(BufReader::new(EXAMPLE_A.as_bytes())).unwrap();
-        assert_eq!(map.solve_p1(), 2);
+        assert_eq!(map.solve_p1().unwrap(), 2);
 
         let map = Map::parse(BufReader::new(EXAMPLE_B.as_bytes())).unwrap();
-        assert_eq!(map.solve_p1(), 6);
+        assert_eq!(map.solve_p1().unwrap(), 6);
+
+        // the ghosts don't start at AAA
+        let map = Map::parse(BufReader::new(EXAMPLE_P2.as_bytes())).unwrap();
+        let e = map.solve_p1().unwrap_err();
+        assert_eq!(e.to_string(), "no node ZZZ in the map");
+    }
+
+    #[test]
+    fn unsolvable_input() {
+        let parse = |s: &str| Map::parse(BufReader::new(s.as_bytes())).unwrap();
+
+        let map = parse("L\n\nZZZ = (ZZZ, ZZZ)\n");
+        assert_eq!(
+            map.solve_p1().unwrap_err().to_string(),
+            "no node AAA in the map"
+        );
+
+        let map = parse("L\n\nAAA = (BBB, BBB)\nZZZ = (ZZZ, ZZZ)\n");
+        assert_eq!(
+            map.solve_p1().unwrap_err().to_string(),
+            "no node BBB in the map"
+        );
+
+        let map = parse("L\n\nAAA = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)\n");
+        assert_eq!(
+            map.solve_p1().unwrap_err().to_string(),
+            "ZZZ can't be reached from AAA"
+        );
+        assert_eq!(
+            map.solve_p2().unwrap_err().to_string(),
+            "no node ending in Z comes round again from AAA"
+        );
+
+        let map = parse("\n");
+        assert_eq!(
+            map.solve_p1().unwrap_err().to_string(),
+            "the map has no instructions"
+        );
+        assert!(map.solve_p2().is_err());
     }
 
     #[test]
     fn example_input_part2() {
         let map = Map::parse(BufReader::new(EXAMPLE_P2.as_bytes())).unwrap();
-        assert_eq!(map.solve_p2(), 6);
+        assert_eq!(map.solve_p2().unwrap(), 6);
+    }
+
+    #[test]
+    fn round_trip() {
+        for example in [EXAMPLE_A, EXAMPLE_B, EXAMPLE_P2] {
+            let map = Map::parse(BufReader::new(example.as_bytes())).unwrap();
+            let text = map.to_string();
+
+            assert_eq!(Map::parse(BufReader::new(text.as_bytes())).unwrap(), map);
+        }
+
+        // the nodes of this one are already in order
+        let map = Map::parse(BufReader::new(EXAMPLE_A.as_bytes())).unwrap();
+        assert_eq!(map.to_string(), EXAMPLE_A);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let e = Node::parse("AAA = (BBB, CCC").unwrap_err();
+        assert_eq!(e.expected(), "`)`");
+
+        let e = Node::parse("AAA = (BBBB, CCC)").unwrap_err();
+        assert_eq!(e.column(), 8);
+        assert_eq!(e.found(), "BBBB");
+
+        let e = Node::parse("AAA").unwrap_err();
+        assert_eq!((e.found(), e.expected()), ("", "`=`"));
+
+        let e = Map::parse(BufReader::new(
+            "LR\n\nAAA = (BBB, CCC)\nBBB = BBB".as_bytes(),
+        ))
+        .unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.line(), Some(4));
+
+        let e = Map::parse(BufReader::new("LXR\n".as_bytes())).unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.column(), 2);
+    }
+
+    #[test]
+    fn generated_input() {
+        let mut knobs = Knobs::new();
+        knobs.set("instructions", 3);
+        knobs.set("ghosts", 2);
+
+        for seed in 0..20 {
+            let input = crate::generate::generator(8)
+                .unwrap()
+                .generate(seed, &knobs)
+                .unwrap();
+            let map = Map::parse(BufReader::new(input.as_bytes())).unwrap();
+
+            // walk all ghosts at once, the slow way
+            let mut ghosts = map
+                .nodes
+                .keys()
+                .copied()
+                .filter(|id| id.is_start())
+                .collect::<Vec<_>>();
+
+            let mut steps = 0;
+
+            for instruction in map.instructions.iter().cycle() {
+                if ghosts.iter().all(|id| id.is_end()) {
+                    break;
+                }
+
+                for id in ghosts.iter_mut() {
+                    *id = map.nodes[id].next(*instruction);
+                }
+
+                steps += 1;
+            }
+
+            assert_eq!(map.solve_p2().unwrap(), steps);
+            assert_eq!(map.solve_p1().unwrap() % 3, 0);
+        }
     }
 }
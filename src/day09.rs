@@ -1,68 +1,229 @@
+use crate::checked::{self, SignedInt};
+use crate::error::{OverflowError, ParseError};
+use crate::generate::{Knob, Knobs};
+use crate::parse::Scanner;
+use crate::rng::Rng;
+use crate::stream;
+use crate::{Solution, Streaming};
+use anyhow::ensure;
+use std::fmt;
+use std::io::BufRead;
 use std::str::FromStr;
 
+#[derive(Debug, PartialEq)]
 pub struct History {
-    values: Vec<i32>,
+    values: Vec<SignedInt>,
 }
 
 impl History {
-    fn derivative(&self) -> Self {
+    fn derivative(&self) -> Result<Self, OverflowError> {
         let mut values = Vec::with_capacity(self.values.len());
 
         for i in 1..self.values.len() {
-            let x = self.values[i] - self.values[i - 1];
+            let x = checked::sub(self.values[i], self.values[i - 1])?;
             values.push(x);
         }
 
-        Self { values }
+        Ok(Self { values })
     }
 
-    pub fn next(&self) -> i32 {
+    pub fn next(&self) -> Result<SignedInt, OverflowError> {
         if self.values.iter().all(|v| *v == 0) {
-            0
+            Ok(0)
         } else {
-            let x = self.values.iter().last().unwrap();
-            x + self.derivative().next()
+            let x = *self.values.iter().last().unwrap();
+            checked::add(x, self.derivative()?.next()?)
         }
     }
 
-    pub fn prev(&self) -> i32 {
+    pub fn prev(&self) -> Result<SignedInt, OverflowError> {
         if self.values.iter().all(|v| *v == 0) {
-            0
+            Ok(0)
         } else {
             let x = self.values[0];
-            x - self.derivative().prev()
+            checked::sub(x, self.derivative()?.prev()?)
         }
     }
 }
 
 impl FromStr for History {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let values = s
-            .split(' ')
-            .map(str::parse::<i32>)
-            .collect::<Result<Vec<_>, _>>()?;
+        let values = Scanner::new(s).integers()?;
         Ok(Self { values })
     }
 }
 
+/// Writes the values separated by single spaces.
+impl fmt::Display for History {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, value) in self.values.iter().enumerate() {
+            let separator = if i > 0 { " " } else { "" };
+            write!(f, "{separator}{value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The report of the OASIS, one value history per line.
+#[derive(Debug)]
+pub struct Report {
+    histories: Vec<History>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for history in self.histories.iter() {
+            writeln!(f, "{history}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Solution for Report {
+    type PartOne = SignedInt;
+    type PartTwo = SignedInt;
+
+    fn parse<R>(reader: R) -> anyhow::Result<Self>
+    where
+        R: BufRead,
+    {
+        let mut histories = Vec::new();
+
+        for (idx, line) in reader.lines().enumerate() {
+            let history = line?.parse::<History>().map_err(|e| e.with_line(idx + 1))?;
+            histories.push(history);
+        }
+
+        Ok(Self { histories })
+    }
+
+    fn part_one(&self) -> anyhow::Result<SignedInt> {
+        Ok(checked::try_sum(self.histories.iter().map(History::next))?)
+    }
+
+    fn part_two(&self) -> anyhow::Result<SignedInt> {
+        Ok(checked::try_sum(self.histories.iter().map(History::prev))?)
+    }
+}
+
+impl Streaming for Report {
+    fn stream<R>(reader: R) -> (anyhow::Result<SignedInt>, anyhow::Result<SignedInt>)
+    where
+        R: BufRead,
+    {
+        stream::split(stream::fold_lines(
+            reader,
+            (0, 0),
+            |(next, prev), n, line| {
+                let history = line.parse::<History>().map_err(|e| e.with_line(n))?;
+                Ok((
+                    checked::add(next, history.next()?)?,
+                    checked::add(prev, history.prev()?)?,
+                ))
+            },
+        ))
+    }
+}
+
+pub const KNOBS: &[Knob] = &[
+    Knob::new("lines", 200, "number of histories"),
+    Knob::new("length", 21, "number of values in a history"),
+    Knob::new(
+        "degree",
+        6,
+        "highest degree of the polynomials behind the values",
+    ),
+];
+
+/// A random history of `len` values, with the value before and after it, and its differences.
+///
+/// The values are those of a polynomial of degree `degree` at most, built up from its constant
+/// differences, so that extrapolating them gives whole numbers. `None` if the values don't fit in
+/// a [`SignedInt`].
+fn random_history(rng: &mut Rng, len: usize, degree: u64) -> Option<Vec<Vec<SignedInt>>> {
+    let degree = rng.range_inclusive(0..=degree) as usize;
+
+    // the first value of each row of differences, the last of which is constant
+    let starts = (0..=degree)
+        .map(|_| rng.range_inclusive(0..=20) as SignedInt - 10)
+        .collect::<Vec<_>>();
+
+    let mut rows = starts.iter().map(|&s| vec![s]).collect::<Vec<_>>();
+
+    for _ in 1..len + 2 {
+        for d in 0..degree {
+            let next = rows[d].last()?.checked_add(*rows[d + 1].last()?)?;
+            rows[d].push(next);
+        }
+
+        rows[degree].push(starts[degree]);
+    }
+
+    Some(rows)
+}
+
+/// Generate a report of histories, whose extrapolations fit in a [`SignedInt`].
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> anyhow::Result<String> {
+    let len = knobs.get("length") as usize;
+    let degree = knobs.get("degree");
+
+    ensure!(len > 0, "histories need at least one value");
+
+    let mut report = String::new();
+    let (mut total_prev, mut total_next): (SignedInt, SignedInt) = (0, 0);
+
+    for _ in 0..knobs.get("lines") {
+        // the differences are all in the rows, so only the totals can still overflow
+        let totals = |rows: &[Vec<SignedInt>]| {
+            let prev = total_prev.checked_add(rows[0][0])?;
+            let next = total_next.checked_add(rows[0][len + 1])?;
+            Some((rows[0].clone(), prev, next))
+        };
+
+        // retry with lower degrees until the numbers are small enough, or give up with zeros
+        let (values, prev, next) = (0..=degree)
+            .rev()
+            .filter_map(|degree| random_history(rng, len, degree))
+            .find_map(|rows| totals(&rows))
+            .unwrap_or_else(|| (vec![0; len + 2], total_prev, total_next));
+
+        total_prev = prev;
+        total_next = next;
+
+        let history = values[1..=len]
+            .iter()
+            .map(SignedInt::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        report += &history;
+        report.push('\n');
+    }
+
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::Op;
     use std::io::{BufRead, BufReader};
 
-    static EXAMPLE: &str = "0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45\n";
+    static EXAMPLE: &str = include_str!("../inputs/day09/examples/example");
 
     #[test]
     fn example_input_part1() {
         let reader = BufReader::new(EXAMPLE.as_bytes());
-        let result: i32 = reader
+        let result: SignedInt = reader
             .lines()
             .map(Result::unwrap)
             .map(|s| s.parse::<History>())
             .map(Result::unwrap)
-            .map(|h| h.next())
+            .map(|h| h.next().unwrap())
             .sum();
         assert_eq!(result, 114);
     }
@@ -70,13 +231,53 @@ mod tests {
     #[test]
     fn example_input_part2() {
         let reader = BufReader::new(EXAMPLE.as_bytes());
-        let result: i32 = reader
+        let result: SignedInt = reader
             .lines()
             .map(Result::unwrap)
             .map(|s| s.parse::<History>())
             .map(Result::unwrap)
-            .map(|h| h.prev())
+            .map(|h| h.prev().unwrap())
             .sum();
         assert_eq!(result, 2);
     }
+
+    #[test]
+    fn invalid_input() {
+        let e = Report::parse(BufReader::new("0 3 6\n1 3 x 10".as_bytes())).unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.line(), Some(2));
+        assert_eq!(e.column(), 5);
+        assert_eq!(e.found(), "x");
+    }
+
+    #[test]
+    fn extra_whitespace() {
+        let history = " 10  13 16\t21 ".parse::<History>().unwrap();
+        assert_eq!(history.values, [10, 13, 16, 21]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let report = Report::parse(BufReader::new(EXAMPLE.as_bytes())).unwrap();
+        assert_eq!(report.to_string(), EXAMPLE);
+
+        let history = " 10  -13 16\t21 ".parse::<History>().unwrap();
+        assert_eq!(history.to_string(), "10 -13 16 21");
+        assert_eq!(history.to_string().parse::<History>().unwrap(), history);
+    }
+
+    #[test]
+    fn overflow() {
+        let (max, min) = (SignedInt::MAX, SignedInt::MIN);
+
+        let report = Report::parse(format!("0 {max}\n").as_bytes()).unwrap();
+        let e = report.part_one().unwrap_err();
+        assert_eq!(e.downcast_ref::<OverflowError>().unwrap().op(), Op::Add);
+
+        let history = format!("{min} 0").parse::<History>().unwrap();
+        assert!(history.prev().is_err());
+
+        let history = format!("{max} {min}").parse::<History>().unwrap();
+        assert!(history.next().is_err());
+    }
 }
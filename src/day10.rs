@@ -1,56 +1,17 @@
-use anyhow::{anyhow, Result};
+use crate::checked::{self, Int};
+use crate::error::ParseError;
+use crate::generate::{Knob, Knobs};
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::normalize::Layout;
+use crate::render::{Canvas, Cell, Color};
+use crate::rng::Rng;
+use crate::Solution;
+use anyhow::{ensure, Result};
 use std::convert::TryFrom;
+use std::fmt;
 use std::io::BufRead;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    const ALL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
-
-    fn inverse(self) -> Self {
-        use Direction::*;
-
-        match self {
-            North => South,
-            East => West,
-            South => North,
-            West => East,
-        }
-    }
-
-    /// If you're walking in direction `self`, this gives the direction you'd go if you turned 90
-    /// degrees to your left.
-    fn left(self) -> Self {
-        use Direction::*;
-
-        match self {
-            North => West,
-            East => North,
-            South => East,
-            West => South,
-        }
-    }
-
-    /// If you're walking in direction `self`, this gives the direction you'd go if you turned 90
-    /// degrees to your right.
-    fn right(self) -> Self {
-        use Direction::*;
-
-        match self {
-            North => East,
-            East => South,
-            South => West,
-            West => North,
-        }
-    }
-}
-
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 struct Pipe(Direction, Direction);
 
@@ -72,21 +33,39 @@ impl Pipe {
         self.0 != self.1.inverse()
     }
 
+    /// Whether moving `from` into this corner, i.e. entering it on its `from.inverse()` end, turns
+    /// right.
     fn is_right_turn(self, from: Direction) -> bool {
         debug_assert!(self.is_corner());
 
-        if self.0 == from.inverse() {
-            self.1 == from.right()
-        } else if self.1 == from.inverse() {
-            self.0 == from.right()
+        let exit = if self.0 == from.inverse() {
+            self.1
         } else {
-            unreachable!()
+            self.0
+        };
+
+        exit == from.right()
+    }
+
+    /// The box drawing character for this pipe.
+    fn box_char(self) -> char {
+        use Direction::*;
+
+        let has = |d| self.0 == d || self.1 == d;
+
+        match (has(North), has(East), has(South), has(West)) {
+            (true, _, true, _) => '│',
+            (_, true, _, true) => '─',
+            (true, true, _, _) => '└',
+            (true, _, _, true) => '┘',
+            (_, true, true, _) => '┌',
+            _ => '┐',
         }
     }
 }
 
 impl TryFrom<char> for Pipe {
-    type Error = anyhow::Error;
+    type Error = ParseError;
 
     fn try_from(c: char) -> std::result::Result<Self, Self::Error> {
         match c {
@@ -96,11 +75,31 @@ impl TryFrom<char> for Pipe {
             'J' => Ok(Self(Direction::North, Direction::West)),
             'F' => Ok(Self(Direction::South, Direction::East)),
             '7' => Ok(Self(Direction::South, Direction::West)),
-            _ => Err(anyhow!("not a pipe: {c}")),
+            _ => Err(ParseError::unexpected(c, "a pipe")),
         }
     }
 }
 
+/// Writes the pipe as it is drawn in the puzzle input, e.g. `L` for a bend north and east.
+impl fmt::Display for Pipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Direction::*;
+
+        let has = |d| self.0 == d || self.1 == d;
+
+        let c = match (has(North), has(East), has(South), has(West)) {
+            (true, _, true, _) => '|',
+            (_, true, _, true) => '-',
+            (true, true, _, _) => 'L',
+            (true, _, _, true) => 'J',
+            (_, true, true, _) => 'F',
+            _ => '7',
+        };
+
+        write!(f, "{c}")
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Node {
     Start,
@@ -122,13 +121,26 @@ impl Node {
 }
 
 impl TryFrom<char> for Node {
-    type Error = anyhow::Error;
+    type Error = ParseError;
 
     fn try_from(c: char) -> std::result::Result<Self, Self::Error> {
         match c {
             'S' => Ok(Self::Start),
             '.' => Ok(Self::Ground),
-            other => Ok(Self::Pipe(Pipe::try_from(other)?)),
+            other => match Pipe::try_from(other) {
+                Ok(pipe) => Ok(Self::Pipe(pipe)),
+                Err(_) => Err(ParseError::unexpected(c, "`S`, `.` or a pipe")),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Start => write!(f, "S"),
+            Self::Ground => write!(f, "."),
+            Self::Pipe(pipe) => write!(f, "{pipe}"),
         }
     }
 }
@@ -143,58 +155,40 @@ enum BoundaryNode {
     /// A corner can only be reached from a non-boundary tile by its two facing directions.
     ///
     /// Either both of these directions are on the outside of the boundary, or both are on the
-    /// inside. So just save whether the reachable directions were left or right.
+    /// inside. So just save whether the reachable directions were left or right, and one of them.
     Corner {
         left: bool,
+        away: Direction,
     },
 }
 
 #[derive(Debug)]
 struct LoopBoundary {
-    width: usize,
-    height: usize,
-    nodes: Vec<Option<BoundaryNode>>,
+    nodes: Grid<Option<BoundaryNode>>,
     left_is_outside: bool,
 }
 
 impl LoopBoundary {
     fn construct(graph: &Graph) -> Self {
-        let mut nodes = Vec::with_capacity(graph.width * graph.height);
-        for _ in 0..(graph.width * graph.height) {
-            nodes.push(None);
-        }
-
-        let loop_tiles = graph.loop_tiles();
+        let mut nodes = Grid::filled(graph.nodes.width(), graph.nodes.height(), None);
 
-        let start_pipe = Pipe(
-            loop_tiles.iter().last().unwrap().2.inverse(),
-            loop_tiles[0].2,
-        );
-
-        for (x, y, dir) in loop_tiles.iter() {
-            let node = graph.get_unchecked(*x, *y);
-            let pipe = match node {
-                Node::Start => start_pipe,
-                Node::Pipe(p) => p,
-                Node::Ground => unreachable!(),
-            };
-
-            let idx = x + y * graph.width;
+        for &(p, dir) in graph.loop_tiles.iter() {
+            let pipe = graph.pipe(p);
 
             if pipe.is_corner() {
-                nodes[idx] = Some(BoundaryNode::Corner {
-                    left: pipe.is_right_turn(*dir),
+                // the corner's two ends point one way each, so the other ways point away from it
+                nodes[p] = Some(BoundaryNode::Corner {
+                    left: pipe.is_right_turn(dir),
+                    away: pipe.0.inverse(),
                 });
             } else {
                 let left = dir.left();
                 let right = dir.right();
-                nodes[idx] = Some(BoundaryNode::Normal { left, right });
+                nodes[p] = Some(BoundaryNode::Normal { left, right });
             }
         }
 
         let mut this = Self {
-            width: graph.width,
-            height: graph.height,
             nodes,
             left_is_outside: false,
         };
@@ -203,73 +197,75 @@ impl LoopBoundary {
         this
     }
 
-    /// Returns true if walking from (x, y) toward `dir` hits the edge of the map without first
+    /// Returns true if walking from `p` toward `dir` hits the edge of the map without first
     /// hitting a boundary node.
-    fn search_for_edge(&self, mut x: usize, mut y: usize, dir: Direction) -> bool {
-        if (x > 0 && x < (self.width - 1)) && (y > 0 && y < (self.height - 1)) {
-            match dir {
-                Direction::North => y -= 1,
-                Direction::East => x += 1,
-                Direction::South => y += 1,
-                Direction::West => x -= 1,
-            };
+    fn search_for_edge(&self, p: Point, dir: Direction) -> bool {
+        if !self.is_at_edge(p) {
+            let p = p.step(dir);
 
-            match self.get(x, y) {
+            match self.get(p) {
                 Some(_) => return false,
-                None => return self.search_for_edge(x, y, dir),
+                None => return self.search_for_edge(p, dir),
             }
         }
 
         true
     }
 
+    fn is_at_edge(&self, p: Point) -> bool {
+        let width = self.nodes.width() as i64;
+        let height = self.nodes.height() as i64;
+
+        p.x == 0 || p.x == width - 1 || p.y == 0 || p.y == height - 1
+    }
+
+    /// Find a side of the loop that some tile of it can see the edge of the map from.
+    ///
+    /// There always is one: nothing is north of the northernmost tiles of the loop.
     fn determine_outside(&mut self) {
-        for x in 0..self.width {
-            for y in 0..self.height {
-                let node = match self.get(x, y) {
+        for x in 0..self.nodes.width() as i64 {
+            for y in 0..self.nodes.height() as i64 {
+                let p = Point::new(x, y);
+
+                let node = match self.get(p) {
                     Some(node) => node,
                     None => continue,
                 };
 
                 match node {
                     BoundaryNode::Normal { left, right } => {
-                        if self.search_for_edge(x, y, *left) {
+                        if self.search_for_edge(p, *left) {
                             self.left_is_outside = true;
                             return;
-                        } else if self.search_for_edge(x, y, *right) {
+                        } else if self.search_for_edge(p, *right) {
                             self.left_is_outside = false;
                             return;
                         }
                     }
 
-                    // this means this won't work for boundaries comprised entirely out of
-                    // corners... oh well
-                    BoundaryNode::Corner { .. } => continue,
+                    BoundaryNode::Corner { left, away } => {
+                        if self.search_for_edge(p, *away) {
+                            self.left_is_outside = *left;
+                            return;
+                        }
+                    }
                 }
             }
         }
-
-        panic!("could not determine which side is outside")
     }
 
-    fn get(&self, x: usize, y: usize) -> Option<&BoundaryNode> {
-        let idx = x + y * self.width;
-        self.nodes[idx].as_ref()
+    fn get(&self, p: Point) -> Option<&BoundaryNode> {
+        self.nodes[p].as_ref()
     }
 
-    fn contains(&self, x: usize, mut y: usize) -> bool {
-        if self.get(x, y).is_some() {
+    fn contains(&self, mut p: Point) -> bool {
+        if self.get(p).is_some() {
             // nodes making up the boundary itself do not count
             return false;
         }
 
-        if x == 0 || x == (self.width - 1) {
-            // at left or right edge, so can't be inside boundary
-            return false;
-        }
-
-        if y == 0 || y == (self.height - 1) {
-            // at top or bottom edge, so can't be inside boundary
+        if self.is_at_edge(p) {
+            // at an edge, so can't be inside boundary
             return false;
         }
 
@@ -279,10 +275,10 @@ impl LoopBoundary {
         //  2) we find a boundary with its inside facing us -> return true
         //  3) we don't find a boundary, so we end up at the edge -> return false
 
-        while y > 0 {
-            y -= 1;
+        while p.y > 0 {
+            p = p.step(Direction::North);
 
-            if let Some(boundary_node) = self.get(x, y) {
+            if let Some(boundary_node) = self.get(p) {
                 let outside = match boundary_node {
                     BoundaryNode::Normal { left, right } => {
                         if self.left_is_outside {
@@ -292,7 +288,7 @@ impl LoopBoundary {
                         }
                     }
 
-                    BoundaryNode::Corner { left } => {
+                    BoundaryNode::Corner { left, .. } => {
                         if self.left_is_outside {
                             *left
                         } else {
@@ -311,71 +307,80 @@ impl LoopBoundary {
 
 #[derive(Debug)]
 pub struct Graph {
-    width: usize,
-    height: usize,
-    nodes: Vec<Node>,
+    nodes: Grid<Node>,
+    /// The tiles of the loop from the start round to it again, with the direction each was
+    /// entered in.
+    loop_tiles: Vec<(Point, Direction)>,
 }
 
 impl Graph {
-    fn get(&self, x: usize, y: usize) -> Option<Node> {
-        let idx = x + y * self.width;
-        self.nodes.get(idx).copied()
+    fn get(&self, p: Point) -> Option<Node> {
+        self.nodes.get(p).copied()
     }
 
-    fn get_unchecked(&self, x: usize, y: usize) -> Node {
-        self.get(x, y).unwrap()
+    fn get_neighbor(&self, p: Point, direction: Direction) -> Option<(Point, Node)> {
+        let p = self.nodes.step(p, direction)?;
+        self.get(p).map(|n| (p, n))
     }
 
-    fn get_neighbor(
-        &self,
-        x: usize,
-        y: usize,
-        direction: Direction,
-    ) -> Option<(usize, usize, Node)> {
-        let (x, y) = match direction {
-            Direction::North if y > 0 => (x, y - 1),
-            Direction::South => (x, y + 1),
-            Direction::East => (x + 1, y),
-            Direction::West if x > 0 => (x - 1, y),
-
-            _ => return None,
-        };
-
-        self.get(x, y).map(|n| (x, y, n))
-    }
-
-    fn find_start(&self) -> (usize, usize) {
-        for x in 0..self.width {
-            for y in 0..self.height {
-                if self.get_unchecked(x, y) == Node::Start {
-                    return (x, y);
-                }
+    /// The pipe on tile `p` of the loop, which for the start is the one that fits in the loop.
+    fn pipe(&self, p: Point) -> Pipe {
+        match self.nodes[p] {
+            Node::Pipe(pipe) => pipe,
+            _ => {
+                let (_, first) = self.loop_tiles[0];
+                let (_, last) = self.loop_tiles[self.loop_tiles.len() - 1];
+                Pipe(last.inverse(), first)
             }
         }
+    }
 
-        panic!("no start node");
+    /// Error at tile `p`, as it is in the puzzle input.
+    fn error_at<S>(&self, p: Point, expected: S) -> ParseError
+    where
+        S: Into<String>,
+    {
+        let line = self
+            .nodes
+            .row(p.y as usize)
+            .iter()
+            .map(Node::to_string)
+            .collect::<String>();
+
+        // all tiles are single bytes
+        let col = p.x as usize;
+        ParseError::at(&line, col, col + 1, expected).with_line(p.y as usize + 1)
     }
 
-    fn loop_tiles(&self) -> Vec<(usize, usize, Direction)> {
-        let mut tiles = Vec::new();
-        let (mut x, mut y) = self.find_start();
+    /// Follow the loop from the start, see [`Graph::loop_tiles`].
+    fn walk(&self) -> Result<Vec<(Point, Direction)>, ParseError> {
+        let Some(mut p) = self
+            .nodes
+            .iter()
+            .find_map(|(pos, node)| (*node == Node::Start).then_some(pos))
+        else {
+            return Err(ParseError::missing_line(
+                self.nodes.height() + 1,
+                "a start tile `S`",
+            ));
+        };
 
-        let mut current = self.get_unchecked(x, y);
+        let mut tiles = Vec::new();
+        let mut current = self.nodes[p];
         let mut last_dir = None;
 
         'outer: loop {
-            'inner: for dir in Direction::ALL {
+            'inner: for dir in Direction::CARDINAL {
                 if Some(dir.inverse()) == last_dir {
                     continue 'inner;
                 }
 
-                match self.get_neighbor(x, y, dir) {
-                    Some((new_x, new_y, neighbor)) => {
+                match self.get_neighbor(p, dir) {
+                    Some((next, neighbor)) => {
                         if current.connects_to(neighbor, dir) {
-                            x = new_x;
-                            y = new_y;
+                            p = next;
 
-                            tiles.push((x, y, dir));
+                            tiles.push((p, dir));
 
                             current = neighbor;
                             last_dir = Some(dir);
@@ -392,58 +397,236 @@ impl Graph {
                 }
             }
 
-            panic!("no neighbor")
+            let expected = match current {
+                Node::Start => "a start between two pipes that connect to it",
+                _ => "a pipe that connects on to the loop",
+            };
+
+            return Err(self.error_at(p, expected));
         }
 
-        tiles
+        Ok(tiles)
     }
 
     pub fn solve_p1(&self) -> usize {
-        self.loop_tiles().len() / 2
+        self.loop_tiles.len() / 2
     }
 
     pub fn solve_p2(&self) -> usize {
         let boundary = LoopBoundary::construct(self);
-        let mut count = 0;
+        self.nodes
+            .iter()
+            .filter(|&(p, _)| boundary.contains(p))
+            .count()
+    }
 
-        for x in 0..self.width {
-            for y in 0..self.height {
-                if boundary.contains(x, y) {
-                    count += 1;
-                }
-            }
+    /// Draw the loop, with the tiles inside it in green and the ones outside it dimmed.
+    fn draw(&self) -> Canvas {
+        let boundary = LoopBoundary::construct(self);
+        let mut canvas = self.nodes.map(|_| Cell::new(' ', Color::Plain));
+
+        for (p, &node) in self.nodes.iter() {
+            let c = match node {
+                Node::Start => 'S',
+                Node::Ground => '.',
+                Node::Pipe(pipe) => pipe.box_char(),
+            };
+
+            let color = if node == Node::Start {
+                Color::Yellow
+            } else if boundary.get(p).is_some() {
+                Color::Cyan
+            } else if boundary.contains(p) {
+                Color::Green
+            } else {
+                Color::Dim
+            };
+
+            canvas[p] = Cell::new(c, color);
         }
 
-        count
+        canvas
     }
+}
 
-    pub fn parse<R>(reader: R) -> Result<Self>
+impl fmt::Display for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.nodes)
+    }
+}
+
+impl Solution for Graph {
+    type PartOne = Int;
+    type PartTwo = Int;
+    const LAYOUT: Layout = Layout::Grid;
+
+    fn parse<R>(reader: R) -> Result<Self>
     where
         R: BufRead,
     {
-        let mut graph = Graph {
-            width: 0,
-            height: 0,
-            nodes: Vec::new(),
+        let mut graph = Self {
+            nodes: Grid::parse(reader, Node::try_from)?,
+            loop_tiles: Vec::new(),
         };
 
-        for line in reader.lines() {
-            let line = line?;
+        graph.loop_tiles = graph.walk()?;
+        Ok(graph)
+    }
 
-            if graph.width == 0 {
-                graph.width = line.len();
-            }
+    fn part_one(&self) -> Result<Int> {
+        Ok(checked::convert(self.solve_p1())?)
+    }
 
-            for c in line.chars() {
-                let node = Node::try_from(c)?;
-                graph.nodes.push(node);
-            }
+    fn part_two(&self) -> Result<Int> {
+        Ok(checked::convert(self.solve_p2())?)
+    }
+
+    fn render(&self) -> Option<Canvas> {
+        Some(self.draw())
+    }
+}
+
+pub const KNOBS: &[Knob] = &[
+    Knob::new("width", 140, "width of the field"),
+    Knob::new("height", 140, "height of the field"),
+    Knob::new(
+        "area",
+        40,
+        "percentage of the field inside the loop, roughly",
+    ),
+    Knob::new(
+        "junk",
+        50,
+        "percentage of tiles off the loop that have a pipe anyway",
+    ),
+];
+
+/// Whether adding the square `p` to `region` keeps its outline a single loop.
+fn can_grow(region: &Grid<bool>, p: Point) -> bool {
+    let inside = |dir: Direction| region.get(p.step(dir)).copied().unwrap_or(false);
+
+    // the squares around `p` must be in one piece, or adding `p` would enclose a hole
+    let ring = Direction::ALL.map(inside);
+    let pieces = (0..8).filter(|&i| ring[i] && !ring[(i + 7) % 8]).count();
+
+    // and where a square only touches `p` at a corner, the loop would cross itself there
+    let pinched = Direction::DIAGONAL
+        .iter()
+        .any(|&d| inside(d) && !inside(d.rotate(-1)) && !inside(d.rotate(1)));
+
+    pieces <= 1 && !pinched
+}
+
+/// A random region of the squares between the tiles of a `width` by `height` field, whose outline
+/// is a single loop through tiles.
+///
+/// The region grows from a single square until it covers `area` percent of the field, or until it
+/// gets stuck.
+fn random_region(rng: &mut Rng, width: usize, height: usize, area: u64) -> Grid<bool> {
+    let mut region = Grid::filled(width - 1, height - 1, false);
+    let target = ((region.width() * region.height()) as u64 * area / 100).max(2);
+
+    let first = Point::new(
+        rng.below(region.width() as u64) as i64,
+        rng.below(region.height() as u64) as i64,
+    );
 
-            graph.height += 1;
+    region[first] = true;
+    let mut squares = vec![first];
+    let mut attempts = 0;
+
+    while (squares.len() as u64) < target && attempts < target * 50 {
+        attempts += 1;
+
+        let from = *rng.choose(&squares);
+
+        let Some(p) = region.step(from, *rng.choose(&Direction::CARDINAL)) else {
+            continue;
+        };
+
+        if !region[p] && can_grow(&region, p) {
+            region[p] = true;
+            squares.push(p);
         }
+    }
 
-        Ok(graph)
+    region
+}
+
+/// The pipes of the loop around `region`, with ground everywhere else.
+///
+/// Tile `(x, y)` is the corner shared by the squares `(x - 1, y - 1)` through `(x, y)`, and has a
+/// pipe toward each neighbour that it shares an edge of the region with.
+fn outline(region: &Grid<bool>) -> Grid<char> {
+    let inside = |x: i64, y: i64| region.get(Point::new(x, y)).copied().unwrap_or(false);
+
+    let mut tiles = Grid::filled(region.width() + 1, region.height() + 1, '.');
+
+    for y in 0..tiles.height() as i64 {
+        for x in 0..tiles.width() as i64 {
+            let north = inside(x - 1, y - 1) != inside(x, y - 1);
+            let east = inside(x, y - 1) != inside(x, y);
+            let south = inside(x - 1, y) != inside(x, y);
+            let west = inside(x - 1, y - 1) != inside(x - 1, y);
+
+            tiles[Point::new(x, y)] = match (north, east, south, west) {
+                (true, false, true, false) => '|',
+                (false, true, false, true) => '-',
+                (true, true, false, false) => 'L',
+                (true, false, false, true) => 'J',
+                (false, false, true, true) => '7',
+                (false, true, true, false) => 'F',
+                _ => '.',
+            };
+        }
+    }
+
+    tiles
+}
+
+/// Generate a field of pipes with a single closed loop through the start, and junk pipes around
+/// it.
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> Result<String> {
+    const PIPES: [char; 6] = ['|', '-', 'L', 'J', '7', 'F'];
+
+    let width = knobs.get("width") as usize;
+    let height = knobs.get("height") as usize;
+    let area = knobs.get("area");
+    let junk = knobs.get("junk");
+
+    ensure!(
+        width >= 2 && height >= 2 && width * height >= 6,
+        "the field is too small for a loop"
+    );
+    ensure!(area <= 100 && junk <= 100, "percentages can't be over 100");
+
+    let region = random_region(rng, width, height, area);
+    let on_loop = outline(&region);
+    let mut tiles = on_loop.clone();
+
+    for (p, &tile) in on_loop.iter() {
+        if tile == '.' && rng.chance(junk as f64 / 100.0) {
+            tiles[p] = *rng.choose(&PIPES);
+        }
+    }
+
+    let loop_tiles = on_loop
+        .iter()
+        .filter(|(_, &tile)| tile != '.')
+        .map(|(p, _)| p)
+        .collect::<Vec<_>>();
+
+    let start = *rng.choose(&loop_tiles);
+    tiles[start] = 'S';
+
+    // only the two neighbours on the loop may connect to the start
+    for p in on_loop.neighbours4(start) {
+        if on_loop[p] == '.' {
+            tiles[p] = '.';
+        }
     }
+
+    Ok(tiles.to_string())
 }
 
 #[cfg(test)]
@@ -451,19 +634,9 @@ mod part1 {
     use super::*;
     use std::io::BufReader;
 
-    static EXAMPLE_SIMPLE: &str = "-L|F7
-7S-7|
-L|7||
--L-J|
-L|-JF
-";
+    static EXAMPLE_SIMPLE: &str = include_str!("../inputs/day10/examples/square-loop");
 
-    static EXAMPLE_COMPLEX: &str = "7-F7-
-.FJ|7
-SJLL7
-|F--J
-LJ.LJ
-";
+    static EXAMPLE_COMPLEX: &str = include_str!("../inputs/day10/examples/complex-loop");
 
     #[test]
     fn get_node() {
@@ -472,11 +645,13 @@ LJ.LJ
         let reader = BufReader::new(EXAMPLE_SIMPLE.as_bytes());
         let graph = Graph::parse(reader).unwrap();
 
-        assert_eq!(graph.get(0, 0), Some(Node::Pipe(Pipe(East, West))));
-        assert_eq!(graph.get(1, 1), Some(Node::Start));
-        assert_eq!(graph.get(2, 2), Some(Node::Pipe(Pipe(South, West))));
-        assert_eq!(graph.get(3, 3), Some(Node::Pipe(Pipe(North, West))));
-        assert_eq!(graph.get(4, 4), Some(Node::Pipe(Pipe(South, East))));
+        let get = |n| graph.get(Point::new(n, n));
+
+        assert_eq!(get(0), Some(Node::Pipe(Pipe(East, West))));
+        assert_eq!(get(1), Some(Node::Start));
+        assert_eq!(get(2), Some(Node::Pipe(Pipe(South, West))));
+        assert_eq!(get(3), Some(Node::Pipe(Pipe(North, West))));
+        assert_eq!(get(4), Some(Node::Pipe(Pipe(South, East))));
     }
 
     #[test]
@@ -491,6 +666,42 @@ LJ.LJ
 
         assert_eq!(graph.solve_p1(), 8);
     }
+
+    #[test]
+    fn round_trip() {
+        for example in [EXAMPLE_SIMPLE, EXAMPLE_COMPLEX] {
+            let graph = Graph::parse(BufReader::new(example.as_bytes())).unwrap();
+            assert_eq!(graph.to_string(), example);
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        let reader = BufReader::new("-L|F7\n7S-X|\n".as_bytes());
+        let e = Graph::parse(reader).unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+
+        assert_eq!(e.line(), Some(2));
+        assert_eq!(e.column(), 4);
+        assert_eq!(e.expected(), "`S`, `.` or a pipe");
+
+        let parse = |s: &str| {
+            let e = Graph::parse(BufReader::new(s.as_bytes())).unwrap_err();
+            e.downcast::<ParseError>().unwrap()
+        };
+
+        let e = parse("F7\nLJ\n");
+        assert_eq!((e.line(), e.expected()), (Some(3), "a start tile `S`"));
+
+        // the loop is broken where the pipe at (3, 2) turns away from it
+        let e = parse(".....\n.S-7.\n.|.L.\n.L-J.\n.....\n");
+        assert_eq!((e.line(), e.column(), e.found()), (Some(3), 4, "L"));
+        assert_eq!(e.expected(), "a pipe that connects on to the loop");
+
+        let e = parse("...\n.S.\n...\n");
+        assert_eq!((e.line(), e.column(), e.found()), (Some(2), 2, "S"));
+        assert_eq!(e.expected(), "a start between two pipes that connect to it");
+    }
 }
 
 #[cfg(test)]
@@ -498,40 +709,11 @@ mod part2 {
     use super::*;
     use std::io::BufReader;
 
-    static EXAMPLE_A: &str = "...........
-.S-------7.
-.|F-----7|.
-.||.....||.
-.||.....||.
-.|L-7.F-J|.
-.|..|.|..|.
-.L--J.L--J.
-...........
-";
+    static EXAMPLE_A: &str = include_str!("../inputs/day10/examples/enclosed-a");
 
-    static EXAMPLE_B: &str = ".F----7F7F7F7F-7....
-.|F--7||||||||FJ....
-.||.FJ||||||||L7....
-FJL7L7LJLJ||LJ.L-7..
-L--J.L7...LJS7F-7L7.
-....F-J..F7FJ|L7L7L7
-....L7.F7||L7|.L7L7|
-.....|FJLJ|FJ|F7|.LJ
-....FJL-7.||.||||...
-....L---J.LJ.LJLJ...
-";
+    static EXAMPLE_B: &str = include_str!("../inputs/day10/examples/enclosed-b");
 
-    static EXAMPLE_C: &str = "FF7FSF7F7F7F7F7F---7
-L|LJ||||||||||||F--J
-FL-7LJLJ||||||LJL-77
-F--JF--7||LJLJ7F7FJ-
-L---JF-JLJ.||-FJLJJ7
-|F|F-JF---7F7-L7L|7|
-|FFJF7L7F-JF7|JL---7
-7-L-JL7||F7|L7F-7F7|
-L.L7LFJ|||||FJL7||LJ
-L7JLJL-JLJLJL--JLJ.L
-";
+    static EXAMPLE_C: &str = include_str!("../inputs/day10/examples/enclosed-c");
 
     static EXAMPLE_D: &str = ".S--7.
 .|..|.
@@ -566,4 +748,66 @@ L7JLJL-JLJLJL--JLJ.L
         let g = mkgraph(EXAMPLE_D);
         assert_eq!(g.solve_p2(), 2);
     }
+
+    #[test]
+    fn only_corners() {
+        // a loop without straight pipes, around a plus sign
+        let g = mkgraph("..F7..\n.FJS7.\n.L7FJ.\n..LJ..\n");
+        assert_eq!(g.solve_p1(), 6);
+        assert_eq!(g.solve_p2(), 0);
+
+        // and around a diamond, with tiles inside
+        let region = Grid::new(
+            5,
+            5,
+            (0..25)
+                .map(|i| (i % 5 - 2i32).abs() + (i / 5 - 2i32).abs() <= 2)
+                .collect(),
+        );
+        let mut tiles = outline(&region);
+        assert!(tiles.iter().all(|(_, &tile)| tile != '|' && tile != '-'));
+
+        let (start, _) = tiles.iter().find(|(_, &tile)| tile != '.').unwrap();
+        tiles[start] = 'S';
+
+        let g = mkgraph(&tiles.to_string());
+        assert_eq!(g.solve_p1(), 10);
+        assert_eq!(g.solve_p2(), 4);
+    }
+
+    #[test]
+    fn render() {
+        let g = mkgraph(EXAMPLE_D);
+        let canvas = g.render().unwrap();
+
+        assert_eq!(canvas.to_string(), ".S──┐.\n.│..│.\n.└──┘.\n");
+
+        let inside = canvas.iter().filter(|(_, cell)| cell.color == Color::Green);
+        assert_eq!(inside.count(), g.solve_p2());
+
+        let on_loop = canvas.iter().filter(|(_, cell)| cell.color == Color::Cyan);
+        assert_eq!(on_loop.count() + 1, 2 * g.solve_p1());
+    }
+
+    #[test]
+    fn generated_loops() {
+        for seed in 0..20 {
+            let mut rng = Rng::new(seed);
+            let region = random_region(&mut rng, 12, 9, 50);
+            let mut tiles = outline(&region);
+
+            let (start, _) = tiles.iter().find(|(_, &tile)| tile != '.').unwrap();
+            tiles[start] = 'S';
+
+            let g = mkgraph(&tiles.to_string());
+
+            // by Pick's theorem, the area of the region is the number of tiles inside the loop
+            // plus half of the number of tiles on it, minus one
+            let area = region.iter().filter(|(_, &inside)| inside).count();
+            let boundary = tiles.iter().filter(|(_, &tile)| tile != '.').count();
+
+            assert_eq!(g.solve_p1(), boundary / 2);
+            assert_eq!(g.solve_p2(), area + 1 - boundary / 2);
+        }
+    }
 }
@@ -1,58 +1,61 @@
-use anyhow::Result;
+use crate::checked::{self, Int};
+use crate::error::{OverflowError, ParseError};
+use crate::generate::{Knob, Knobs};
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::normalize::Layout;
+use crate::render::{Canvas, Cell, Color};
+use crate::rng::Rng;
+use crate::Solution;
+use anyhow::{ensure, Result};
 use std::collections::HashSet;
+use std::fmt;
 use std::io::BufRead;
 
-#[derive(Debug, PartialEq, Eq, Hash)]
-struct Galaxy {
-    x: usize,
-    y: usize,
-}
-
-impl Galaxy {
-    fn shortest_path(&self, other: &Self) -> usize {
-        // Since we can't walk diagonally, the shortest path is just the sum of the distances in
-        // each dimension.
-        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
-    }
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Universe {
-    galaxies: Vec<Galaxy>,
+    galaxies: Vec<Point>,
+    empty_rows: Vec<i64>,
+    empty_columns: Vec<i64>,
+
+    /// Size of the image, before expanding.
     width: usize,
     height: usize,
 }
 
 impl Universe {
-    fn expand_row(&mut self, row: usize, n: usize) {
-        self.galaxies
-            .iter_mut()
-            .filter(|g| g.y > row)
-            .for_each(|g| g.y += n);
+    fn expand_row(&mut self, row: i64, n: i64) -> Result<(), OverflowError> {
+        for g in self.galaxies.iter_mut().filter(|g| g.y > row) {
+            g.y = checked::add(g.y, n)?;
+        }
+
+        Ok(())
     }
 
-    fn expand_column(&mut self, column: usize, n: usize) {
-        self.galaxies
-            .iter_mut()
-            .filter(|g| g.x > column)
-            .for_each(|g| g.x += n);
+    fn expand_column(&mut self, column: i64, n: i64) -> Result<(), OverflowError> {
+        for g in self.galaxies.iter_mut().filter(|g| g.x > column) {
+            g.x = checked::add(g.x, n)?;
+        }
+
+        Ok(())
     }
 
-    pub fn expand(&mut self, n: usize) {
-        for row in (0..self.height).rev() {
-            if !self.galaxies.iter().any(|g| g.y == row) {
-                self.expand_row(row, n);
-            }
+    /// Add `n` rows after each row without galaxies, and `n` columns after each such column.
+    pub fn expand(&mut self, n: usize) -> Result<(), OverflowError> {
+        let n = checked::convert(n)?;
+
+        for row in self.empty_rows.clone().into_iter().rev() {
+            self.expand_row(row, n)?;
         }
 
-        for column in (0..self.width).rev() {
-            if !self.galaxies.iter().any(|g| g.x == column) {
-                self.expand_column(column, n);
-            }
+        for column in self.empty_columns.clone().into_iter().rev() {
+            self.expand_column(column, n)?;
         }
+
+        Ok(())
     }
 
-    pub fn solve(&self) -> usize {
+    pub fn solve(&self) -> Result<Int, OverflowError> {
         let mut done_pairs = HashSet::new();
         let mut sum = 0;
 
@@ -66,49 +69,184 @@ impl Universe {
                     continue;
                 }
 
-                sum += a.shortest_path(b);
+                // we can't walk diagonally, and as galaxies are never at negative coordinates,
+                // their distance fits in a u64
+                sum = checked::add(sum, Int::from(a.manhattan(*b)))?;
                 done_pairs.insert((a, b));
             }
         }
 
-        sum
+        Ok(sum)
     }
 
-    pub fn parse<R>(reader: R) -> Result<Self>
-    where
-        R: BufRead,
-    {
-        let mut galaxies = Vec::new();
-        let mut width = 0;
+    /// Sum of shortest paths between all galaxies, after replacing each empty row and column by
+    /// `factor` empty rows or columns.
+    fn solve_expanded(&self, factor: usize) -> Result<Int, OverflowError> {
+        let mut universe = self.clone();
+        universe.expand(factor - 1)?;
+        universe.solve()
+    }
 
-        let mut x = 0;
-        let mut y = 0;
+    /// Draw the universe as expanded for part one, with the rows and columns added in blue.
+    fn draw(&self) -> Canvas {
+        let mut universe = self.clone();
+        universe
+            .expand(1)
+            .expect("the galaxies of an image in memory can be moved by one more row or column");
 
-        for line in reader.lines() {
-            let line = line?;
+        let width = self.width + self.empty_columns.len();
+        let height = self.height + self.empty_rows.len();
 
-            if width == 0 {
-                width = line.len();
-            }
+        // after expanding, the rows and columns without galaxies are the empty ones and the copies
+        let mut empty_rows = vec![true; height];
+        let mut empty_columns = vec![true; width];
 
-            for chr in line.chars() {
-                if chr == '#' {
-                    galaxies.push(Galaxy { x, y });
-                }
+        for g in universe.galaxies.iter() {
+            empty_rows[g.y as usize] = false;
+            empty_columns[g.x as usize] = false;
+        }
+
+        let mut canvas = Grid::filled(width, height, Cell::new('.', Color::Dim));
 
-                x += 1;
+        for y in 0..height {
+            for x in 0..width {
+                if empty_rows[y] || empty_columns[x] {
+                    canvas[Point::new(x as i64, y as i64)].color = Color::Blue;
+                }
             }
+        }
+
+        for &g in universe.galaxies.iter() {
+            canvas[g] = Cell::new('#', Color::Yellow);
+        }
+
+        canvas
+    }
+}
+
+/// Writes the image, grown to fit any galaxies that expanding moved out of it.
+impl fmt::Display for Universe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (width, height) = self
+            .galaxies
+            .iter()
+            .fold((self.width, self.height), |(w, h), g| {
+                (w.max(g.x as usize + 1), h.max(g.y as usize + 1))
+            });
 
-            x = 0;
-            y += 1;
+        let mut image = Grid::filled(width, height, '.');
+
+        for &g in self.galaxies.iter() {
+            image[g] = '#';
         }
 
+        write!(f, "{image}")
+    }
+}
+
+impl Solution for Universe {
+    type PartOne = Int;
+    type PartTwo = Int;
+    const LAYOUT: Layout = Layout::Grid;
+
+    fn parse<R>(reader: R) -> Result<Self>
+    where
+        R: BufRead,
+    {
+        let image = Grid::parse(reader, |c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(ParseError::unexpected(c, "`.` or `#`")),
+        })?;
+
+        let galaxies = image
+            .iter()
+            .filter(|(_, &galaxy)| galaxy)
+            .map(|(p, _)| p)
+            .collect();
+
+        let empty_rows = (0..image.height())
+            .filter(|&y| !image.row(y).contains(&true))
+            .map(|y| y as i64)
+            .collect();
+
+        let empty_columns = (0..image.width())
+            .filter(|&x| !image.column(x).any(|&galaxy| galaxy))
+            .map(|x| x as i64)
+            .collect();
+
         Ok(Self {
             galaxies,
-            width,
-            height: y,
+            empty_rows,
+            empty_columns,
+            width: image.width(),
+            height: image.height(),
         })
     }
+
+    fn part_one(&self) -> Result<Int> {
+        Ok(self.solve_expanded(2)?)
+    }
+
+    fn part_two(&self) -> Result<Int> {
+        Ok(self.solve_expanded(1_000_000)?)
+    }
+
+    fn render(&self) -> Option<Canvas> {
+        Some(self.draw())
+    }
+}
+
+pub const KNOBS: &[Knob] = &[
+    Knob::new("width", 140, "width of the image"),
+    Knob::new("height", 140, "height of the image"),
+    Knob::new("galaxies", 440, "number of galaxies"),
+    Knob::new(
+        "empty",
+        5,
+        "percentage of rows and columns without galaxies",
+    ),
+];
+
+/// Generate an image of galaxies, keeping some rows and columns empty.
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> Result<String> {
+    let width = knobs.get("width") as usize;
+    let height = knobs.get("height") as usize;
+    let galaxies = knobs.get("galaxies") as usize;
+    let empty = knobs.get("empty") as usize;
+
+    ensure!(width > 0 && height > 0, "the image can't be empty");
+    ensure!(empty <= 100, "percentages can't be over 100");
+
+    let mut pick = |n: usize| {
+        let mut lines = (0..n as i64).collect::<Vec<_>>();
+        rng.shuffle(&mut lines);
+        lines.truncate(n - n * empty / 100);
+        lines
+    };
+
+    let rows = pick(height);
+    let columns = pick(width);
+
+    ensure!(
+        galaxies <= rows.len() * columns.len(),
+        "there are only {} places for galaxies",
+        rows.len() * columns.len()
+    );
+
+    let mut image = Grid::filled(width, height, '.');
+    let mut placed = 0;
+
+    while placed < galaxies {
+        let p = Point::new(*rng.choose(&columns), *rng.choose(&rows));
+
+        if image[p] == '.' {
+            image[p] = '#';
+            placed += 1;
+        }
+    }
+
+    Ok(image.to_string())
 }
 
 #[cfg(test)]
@@ -116,17 +254,7 @@ mod tests {
     use super::*;
     use std::io::BufReader;
 
-    static EXAMPLE: &str = "...#......
-.......#..
-#.........
-..........
-......#...
-.#........
-.........#
-..........
-.......#..
-#...#.....
-";
+    static EXAMPLE: &str = include_str!("../inputs/day11/examples/example");
 
     static EXAMPLE_EXPANDED: &str = "....#........
 .........#...
@@ -150,29 +278,68 @@ mod tests {
     #[test]
     fn test_expansion() {
         let mut universe = mk_universe(EXAMPLE);
-        universe.expand(1);
+        universe.expand(1).unwrap();
 
         let expanded = mk_universe(EXAMPLE_EXPANDED);
 
         assert_eq!(universe.galaxies, expanded.galaxies);
     }
 
+    #[test]
+    fn round_trip() {
+        let mut universe = mk_universe(EXAMPLE);
+        assert_eq!(universe.to_string(), EXAMPLE);
+
+        universe.expand(1).unwrap();
+        assert_eq!(universe.to_string(), EXAMPLE_EXPANDED);
+    }
+
     #[test]
     fn example_input_p1() {
         let mut universe = mk_universe(EXAMPLE);
-        universe.expand(1);
+        universe.expand(1).unwrap();
 
-        assert_eq!(universe.solve(), 374);
+        assert_eq!(universe.solve().unwrap(), 374);
     }
 
     #[test]
     fn example_input_p2() {
         let mut universe = mk_universe(EXAMPLE);
-        universe.expand(9);
-        assert_eq!(universe.solve(), 1030);
+        universe.expand(9).unwrap();
+        assert_eq!(universe.solve().unwrap(), 1030);
+
+        let mut universe = mk_universe(EXAMPLE);
+        universe.expand(99).unwrap();
+        assert_eq!(universe.solve().unwrap(), 8410);
+    }
 
+    #[test]
+    fn expanding_too_far() {
         let mut universe = mk_universe(EXAMPLE);
-        universe.expand(99);
-        assert_eq!(universe.solve(), 8410);
+        assert!(universe.expand(i64::MAX as usize).is_err());
+        assert!(universe.expand(usize::MAX).is_err());
+
+        let e = universe.solve_expanded(i64::MAX as usize / 2).unwrap_err();
+        assert_eq!(e.ty(), "i64");
+    }
+
+    #[test]
+    fn render() {
+        let canvas = mk_universe(EXAMPLE).render().unwrap();
+        assert_eq!(canvas.to_string(), EXAMPLE_EXPANDED);
+
+        // two empty rows and three empty columns, all doubled
+        let added = canvas.iter().filter(|(_, cell)| cell.color == Color::Blue);
+        assert_eq!(added.count(), 4 * 13 + 6 * 12 - 4 * 6);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let reader = BufReader::new("...#\n.*..\n".as_bytes());
+        let e = Universe::parse(reader).unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+
+        assert_eq!(e.line(), Some(2));
+        assert_eq!(e.column(), 2);
     }
 }
@@ -1,10 +1,18 @@
-use anyhow::{anyhow, Context, Result};
+use crate::checked::{self, Int};
+use crate::error::{OverflowError, ParseError, Unsolved};
+use crate::generate::{Knob, Knobs};
+use crate::parse::Scanner;
+use crate::rng::Rng;
+use crate::stream;
+use crate::{Solution, Streaming};
+use anyhow::{ensure, Result};
+use std::fmt;
 use std::io::BufRead;
+use std::str::FromStr;
 
 trait BitOps: Copy {
     fn bit(self, n: Self) -> bool;
     fn set_bit(&mut self, n: Self);
-    fn clear_bit(&mut self, n: Self);
 }
 
 impl BitOps for u32 {
@@ -19,12 +27,6 @@ impl BitOps for u32 {
 
         *self |= 1 << n
     }
-
-    fn clear_bit(&mut self, n: Self) {
-        debug_assert!(n < Self::BITS);
-
-        *self &= !(1 << n)
-    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -34,8 +36,21 @@ enum Spring {
     Unknown,
 }
 
-#[derive(Debug)]
+impl fmt::Display for Spring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match self {
+            Self::Intact => '.',
+            Self::Broken => '#',
+            Self::Unknown => '?',
+        };
+
+        write!(f, "{c}")
+    }
+}
+
+#[derive(Debug, PartialEq)]
 pub struct Record {
+    springs: Vec<Spring>,
     arrangements: Vec<u32>,
     total: u32,
     lower: u32,
@@ -92,11 +107,13 @@ impl Record {
         true
     }
 
-    pub fn permutations(&self) -> u32 {
-        (self.lower..=self.upper)
+    pub fn permutations(&self) -> Result<Int, OverflowError> {
+        let count = (self.lower..=self.upper)
             .map(|n| self.matches(n))
             .filter(|b| *b)
-            .count() as u32
+            .count();
+
+        checked::convert(count)
     }
 
     pub fn parse<R>(reader: R) -> Result<Vec<Self>>
@@ -104,75 +121,229 @@ impl Record {
         R: BufRead,
     {
         let mut records = Vec::new();
-        let mut springs = Vec::new();
 
-        for line in reader.lines() {
-            let line = line?;
+        for (idx, line) in reader.lines().enumerate() {
+            let record = line?.parse::<Self>().map_err(|e| e.with_line(idx + 1))?;
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+}
+
+impl FromStr for Record {
+    type Err = ParseError;
 
-            let mut components = line.split(' ');
+    /// Parse a record like `???.### 1,1,3`.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(s);
+        let conditions = scanner.until(" ")?.rest();
 
-            for chr in components.next().context("invalid input")?.chars() {
-                match chr {
-                    '.' => springs.push(Spring::Intact),
-                    '#' => springs.push(Spring::Broken),
-                    '?' => springs.push(Spring::Unknown),
-                    _ => return Err(anyhow!("invalid input")),
+        let mut springs = Vec::new();
+
+        for (col, chr) in conditions.char_indices() {
+            match chr {
+                '.' => springs.push(Spring::Intact),
+                '#' => springs.push(Spring::Broken),
+                '?' => springs.push(Spring::Unknown),
+                _ => {
+                    return Err(ParseError::at(
+                        s,
+                        col,
+                        col + chr.len_utf8(),
+                        "`.`, `#` or `?`",
+                    ))
                 }
             }
+        }
 
-            let mut lower = 0;
-            let mut upper = 0;
+        // part one tries all arrangements as bits of a `u32`
+        if springs.len() > u32::BITS as usize {
+            let start = u32::BITS as usize;
+            return Err(ParseError::at(
+                s,
+                start,
+                conditions.len(),
+                "at most 32 springs",
+            ));
+        }
 
-            for (bit, spring) in springs.iter().rev().enumerate() {
-                match spring {
-                    Spring::Broken => {
-                        lower.set_bit(bit as u32);
-                        upper.set_bit(bit as u32);
-                    }
+        let mut lower = 0;
+        let mut upper = 0;
 
-                    Spring::Unknown => {
-                        upper.set_bit(bit as u32);
-                    }
+        for (bit, spring) in springs.iter().rev().enumerate() {
+            match spring {
+                Spring::Broken => {
+                    lower.set_bit(bit as u32);
+                    upper.set_bit(bit as u32);
+                }
 
-                    _ => {}
+                Spring::Unknown => {
+                    upper.set_bit(bit as u32);
                 }
+
+                _ => {}
+            }
+        }
+
+        let mut arrangements = Vec::new();
+
+        loop {
+            arrangements.push(scanner.integer("a group size")?);
+
+            if !scanner.eat(",") {
+                scanner.end()?;
+                break;
             }
+        }
 
-            let arrangements = components
-                .next()
-                .context("invalid input")?
-                .split(',')
-                .map(|s| s.parse::<u32>())
-                .collect::<std::result::Result<Vec<_>, _>>()?;
+        let total = arrangements.iter().sum();
 
-            let total = arrangements.iter().sum();
+        Ok(Self {
+            springs,
+            arrangements,
+            total,
+            lower,
+            upper,
+        })
+    }
+}
 
-            records.push(Record {
-                arrangements,
-                total,
-                lower,
-                upper,
-            });
+/// Writes a record like `???.### 1,1,3`.
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for spring in self.springs.iter() {
+            write!(f, "{spring}")?;
+        }
 
-            springs.clear();
+        for (i, group) in self.arrangements.iter().enumerate() {
+            let separator = if i > 0 { "," } else { " " };
+            write!(f, "{separator}{group}")?;
         }
 
-        Ok(records)
+        Ok(())
     }
 }
 
+#[derive(Debug)]
+pub struct Records {
+    records: Vec<Record>,
+}
+
+impl fmt::Display for Records {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for record in self.records.iter() {
+            writeln!(f, "{record}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Solution for Records {
+    type PartOne = Int;
+    type PartTwo = Int;
+
+    fn parse<R>(reader: R) -> Result<Self>
+    where
+        R: BufRead,
+    {
+        let records = Record::parse(reader)?;
+        Ok(Self { records })
+    }
+
+    fn part_one(&self) -> Result<Int> {
+        Ok(checked::try_sum(
+            self.records.iter().map(Record::permutations),
+        )?)
+    }
+
+    fn part_two(&self) -> Result<Int> {
+        Err(Unsolved.into())
+    }
+}
+
+impl Streaming for Records {
+    fn stream<R>(reader: R) -> (Result<Int>, Result<Int>)
+    where
+        R: BufRead,
+    {
+        let one = stream::fold_lines(reader, 0, |total, n, line| {
+            let record = line.parse::<Record>().map_err(|e| e.with_line(n))?;
+            Ok(checked::add(total, record.permutations()?)?)
+        });
+
+        (one, Err(Unsolved.into()))
+    }
+}
+
+pub const KNOBS: &[Knob] = &[
+    Knob::new("records", 1000, "number of records"),
+    Knob::new(
+        "length",
+        20,
+        "maximum number of springs in a record, at most 32",
+    ),
+    Knob::new(
+        "unknown",
+        50,
+        "percentage of springs whose condition is unknown",
+    ),
+];
+
+/// Generate condition records, each of which has at least one arrangement.
+pub fn generate(rng: &mut Rng, knobs: &Knobs) -> Result<String> {
+    let length = knobs.get("length");
+    let unknown = knobs.get("unknown");
+
+    // part one tries all arrangements as bits of a `u32`
+    ensure!(
+        (1..=u32::BITS as u64).contains(&length),
+        "records can have 1 to 32 springs"
+    );
+    ensure!(unknown <= 100, "percentages can't be over 100");
+
+    let mut records = String::new();
+
+    for _ in 0..knobs.get("records") {
+        let n = rng.range_inclusive(1..=length) as usize;
+        let mut broken = (0..n).map(|_| rng.chance(0.5)).collect::<Vec<_>>();
+
+        // there must be at least one group
+        if !broken.contains(&true) {
+            broken[rng.below(n as u64) as usize] = true;
+        }
+
+        let groups = broken
+            .split(|&b| !b)
+            .filter(|group| !group.is_empty())
+            .map(|group| group.len().to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let springs = broken
+            .iter()
+            .map(|&b| match b {
+                _ if rng.chance(unknown as f64 / 100.0) => '?',
+                true => '#',
+                false => '.',
+            })
+            .collect::<String>();
+
+        records += &format!("{springs} {groups}\n");
+    }
+
+    Ok(records)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::prop;
+    use crate::rng::Rng;
     use std::io::BufReader;
 
-    static EXAMPLE: &str = "???.### 1,1,3
-.??..??...?##. 1,1,3
-?#?#?#?#?#?#?#? 1,3,1,6
-????.#...#... 4,1,1
-????.######..#####. 1,6,5
-?###???????? 3,2,1
-";
+    static EXAMPLE: &str = include_str!("../inputs/day12/examples/example");
 
     #[test]
     fn example_input() {
@@ -181,14 +352,112 @@ mod tests {
 
         for (idx, record) in records.iter().enumerate() {
             match idx {
-                0 => assert_eq!(record.permutations(), 1),
-                1 => assert_eq!(record.permutations(), 4),
-                2 => assert_eq!(record.permutations(), 1),
-                3 => assert_eq!(record.permutations(), 1),
-                4 => assert_eq!(record.permutations(), 4),
-                5 => assert_eq!(record.permutations(), 10),
+                0 => assert_eq!(record.permutations().unwrap(), 1),
+                1 => assert_eq!(record.permutations().unwrap(), 4),
+                2 => assert_eq!(record.permutations().unwrap(), 1),
+                3 => assert_eq!(record.permutations().unwrap(), 1),
+                4 => assert_eq!(record.permutations().unwrap(), 4),
+                5 => assert_eq!(record.permutations().unwrap(), 10),
                 _ => unreachable!(),
             }
         }
     }
+
+    #[test]
+    fn round_trip() {
+        let records = Records::parse(BufReader::new(EXAMPLE.as_bytes())).unwrap();
+        assert_eq!(records.to_string(), EXAMPLE);
+
+        let record = "#.?  1, 1".parse::<Record>().unwrap();
+        assert_eq!(record.to_string(), "#.? 1,1");
+        assert_eq!(record.to_string().parse::<Record>().unwrap(), record);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let reader = BufReader::new("???.### 1,1,3\n.??..?x...?##. 1,1,3\n".as_bytes());
+        let e = Record::parse(reader).unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.line(), Some(2));
+        assert_eq!(e.column(), 7);
+
+        let reader = BufReader::new("???.### 1,,3\n".as_bytes());
+        let e = Record::parse(reader).unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.column(), 11);
+        assert_eq!(e.expected(), "a group size");
+
+        let springs = "?".repeat(32);
+        assert!(format!("{springs} 1,1").parse::<Record>().is_ok());
+
+        let e = format!("{springs}? 1,1").parse::<Record>().unwrap_err();
+        assert_eq!((e.column(), e.found()), (33, "?"));
+        assert_eq!(e.expected(), "at most 32 springs");
+    }
+
+    /// The number of arrangements of `springs` that match `groups`, by trying every condition of
+    /// the unknown springs.
+    fn brute_force(springs: &str, groups: &[u32]) -> usize {
+        let unknown = springs.matches('?').count();
+
+        (0..1u32 << unknown)
+            .filter(|&mask| {
+                let mut bits = (0..unknown).map(|bit| mask & (1 << bit) != 0);
+
+                let broken = springs
+                    .chars()
+                    .map(|c| match c {
+                        '?' => bits.next().unwrap(),
+                        _ => c == '#',
+                    })
+                    .collect::<Vec<_>>();
+
+                let found = broken
+                    .split(|&b| !b)
+                    .filter(|group| !group.is_empty())
+                    .map(|group| group.len() as u32)
+                    .collect::<Vec<_>>();
+
+                found == groups
+            })
+            .count()
+    }
+
+    #[test]
+    fn permutations_agree_with_brute_force() {
+        let springs = |rng: &mut Rng| {
+            let n = rng.range_inclusive(1..=12);
+            (0..n).map(|_| rng.below(3) as u8).collect::<Vec<_>>()
+        };
+
+        let groups = |rng: &mut Rng| {
+            let n = rng.range_inclusive(1..=4);
+            (0..n).map(|_| rng.below(4) as u32).collect::<Vec<_>>()
+        };
+
+        prop::check(
+            |rng| (springs(rng), groups(rng)),
+            |(springs, groups)| {
+                if groups.is_empty() {
+                    return true;
+                }
+
+                let springs = springs
+                    .iter()
+                    .map(|&s| ['.', '#', '?'][s as usize % 3])
+                    .collect::<String>();
+                let groups = groups
+                    .iter()
+                    .map(|g| (g + 1).to_string())
+                    .collect::<Vec<_>>()
+                    .join(",");
+
+                let line = format!("{springs} {groups}");
+                let record = &Record::parse(line.as_bytes()).unwrap()[0];
+
+                record.permutations().ok()
+                    == Some(brute_force(&springs, &record.arrangements) as Int)
+            },
+        );
+    }
 }
@@ -0,0 +1,311 @@
+//! Errors with enough context to point at the offending part of a puzzle input.
+
+use std::fmt;
+
+/// A syntax error in a puzzle input.
+///
+/// Records the offending line, where on it the problem is and what was expected there, and renders
+/// all of that as a diagnostic:
+///
+/// ```text
+/// line 3, column 10: expected a number, found `x1`
+///   |
+/// 3 | Card 3:  x1 21 53 59 44 | 69 82 63 72 16 21 14  1
+///   |          ^^
+/// ```
+///
+/// Parsers for a single line don't know which line they are parsing; loaders add that with
+/// [`ParseError::with_line`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    line: Option<usize>,
+    text: String,
+    /// Byte range of the offending text in `text`.
+    start: usize,
+    end: usize,
+    expected: String,
+    /// Whether the input ended before the line this error is about.
+    eof: bool,
+}
+
+impl ParseError {
+    /// Error in `text` at byte range `start..end`.
+    pub fn at<S>(text: &str, start: usize, end: usize, expected: S) -> Self
+    where
+        S: Into<String>,
+    {
+        debug_assert!(start <= end && end <= text.len());
+
+        Self {
+            line: None,
+            text: text.to_owned(),
+            start,
+            end,
+            expected: expected.into(),
+            eof: false,
+        }
+    }
+
+    /// Error in `text`, where `found` is the offending part of it.
+    ///
+    /// `found` should be a slice of `text`. If it isn't, its first occurrence in `text` is used.
+    pub fn new<S>(text: &str, found: &str, expected: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let text_start = text.as_ptr() as usize;
+        let found_start = found.as_ptr() as usize;
+
+        let start =
+            if found_start >= text_start && found_start + found.len() <= text_start + text.len() {
+                found_start - text_start
+            } else {
+                text.find(found).unwrap_or(text.len())
+            };
+
+        let end = (start + found.len()).min(text.len());
+        Self::at(text, start, end, expected)
+    }
+
+    /// Error at the end of `text`, which ended before something `expected` came up.
+    pub fn at_end<S>(text: &str, expected: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::at(text, text.len(), text.len(), expected)
+    }
+
+    /// Error for a single character `c`, with no surrounding text.
+    ///
+    /// Meant for parsers of single characters; whatever reads the line moves the error to where
+    /// `c` is on it.
+    pub fn unexpected<S>(c: char, expected: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let text = c.to_string();
+        Self::at(&text, 0, text.len(), expected)
+    }
+
+    /// Error for a line that was expected, but the input ended before it.
+    pub fn missing_line<S>(line: usize, expected: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let mut e = Self::at("", 0, 0, expected).with_line(line);
+        e.eof = true;
+        e
+    }
+
+    /// Set the (1-based) number of the line this error is on.
+    pub fn with_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// The 1-based line number, if known.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// The 1-based column, counted in characters.
+    pub fn column(&self) -> usize {
+        self.text[..self.start].chars().count() + 1
+    }
+
+    /// The offending text.
+    pub fn found(&self) -> &str {
+        &self.text[self.start..self.end]
+    }
+
+    /// What was expected instead.
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.eof {
+            if let Some(line) = self.line {
+                write!(f, "line {line}: ")?;
+            }
+
+            return write!(f, "expected {}, found end of input", self.expected);
+        }
+
+        if let Some(line) = self.line {
+            write!(f, "line {line}, ")?;
+        }
+
+        write!(f, "column {}: expected {}, ", self.column(), self.expected)?;
+
+        match self.found() {
+            "" => writeln!(f, "found end of line")?,
+            found => writeln!(f, "found `{found}`")?,
+        }
+
+        let gutter = self.line.map(|l| l.to_string()).unwrap_or_default();
+        let pad = " ".repeat(gutter.len());
+
+        let indent = " ".repeat(self.column() - 1);
+        let carets = "^".repeat(self.found().chars().count().max(1));
+
+        writeln!(f, "{pad} |")?;
+        writeln!(f, "{gutter} | {}", self.text)?;
+        write!(f, "{pad} | {indent}{carets}")
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An arithmetic operation, see [`OverflowError`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Convert,
+}
+
+/// An integer too big (or small) for its type, from arithmetic in [`crate::checked`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OverflowError {
+    op: Op,
+    ty: &'static str,
+}
+
+impl OverflowError {
+    /// Overflow of `op` on integers of type `ty`, e.g. `u64`.
+    pub fn new(op: Op, ty: &'static str) -> Self {
+        Self { op, ty }
+    }
+
+    pub fn op(&self) -> Op {
+        self.op
+    }
+
+    /// The type that overflowed, e.g. `u64`.
+    pub fn ty(&self) -> &'static str {
+        self.ty
+    }
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ty = self.ty;
+
+        match self.op {
+            Op::Add => write!(f, "{ty} addition overflowed")?,
+            Op::Sub => write!(f, "{ty} subtraction overflowed")?,
+            Op::Mul => write!(f, "{ty} multiplication overflowed")?,
+            Op::Convert => write!(f, "number does not fit in {ty}")?,
+        }
+
+        if !crate::checked::WIDE {
+            write!(f, ", the `wide` feature computes answers in 128 bits")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for OverflowError {}
+
+/// A part of a puzzle that isn't solved yet, which [`Solution`](crate::Solution)s return instead
+/// of an answer.
+///
+/// It isn't a failure: `aoc` reports such parts as unsolved, and doesn't fail because of them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Unsolved;
+
+impl Unsolved {
+    /// Whether `e` is an [`Unsolved`] part, with or without context.
+    pub fn is(e: &anyhow::Error) -> bool {
+        e.downcast_ref::<Self>().is_some()
+    }
+}
+
+impl fmt::Display for Unsolved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not solved yet")
+    }
+}
+
+impl std::error::Error for Unsolved {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let line = "Card 3:  x1 21 53";
+        let found = &line[9..11];
+
+        let e = ParseError::new(line, found, "a number").with_line(3);
+
+        assert_eq!(e.line(), Some(3));
+        assert_eq!(e.column(), 10);
+        assert_eq!(e.found(), "x1");
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 10: expected a number, found `x1`
+  |
+3 | Card 3:  x1 21 53
+  |          ^^"
+        );
+    }
+
+    #[test]
+    fn render_at_end() {
+        let e = ParseError::at_end("AAA = (BBB", "`,`");
+
+        assert_eq!(e.column(), 11);
+        assert_eq!(
+            e.to_string(),
+            "column 11: expected `,`, found end of line
+ |
+ | AAA = (BBB
+ |           ^"
+        );
+    }
+
+    #[test]
+    fn render_missing_line() {
+        let e = ParseError::missing_line(2, "distances");
+        assert_eq!(
+            e.to_string(),
+            "line 2: expected distances, found end of input"
+        );
+    }
+
+    #[test]
+    fn not_a_slice() {
+        let e = ParseError::new("a b c", "c", "d");
+        assert_eq!(e.column(), 5);
+
+        let e = ParseError::new("a b c", "x", "d");
+        assert_eq!(e.column(), 6);
+        assert_eq!(e.found(), "");
+    }
+
+    #[test]
+    fn unsolved() {
+        let e = anyhow::Error::from(Unsolved).context("part 2");
+        assert!(Unsolved::is(&e));
+        assert!(!Unsolved::is(&anyhow::anyhow!("not solved yet")));
+    }
+
+    #[test]
+    fn empty_line() {
+        let e = ParseError::at_end("", "a card").with_line(4);
+        assert_eq!(
+            e.to_string(),
+            "line 4, column 1: expected a card, found end of line
+  |
+4 | 
+  | ^"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Example inputs from the puzzle descriptions, stored as files along with their answers.
+//!
+//! Examples for a day live in `examples/` next to its input, one file each, with their answers
+//! next to them in the format described in [`crate::answers`]:
+//!
+//! ```text
+//! inputs/day08/examples/ghosts
+//! inputs/day08/examples/ghosts.answers
+//! ```
+//!
+//! The `examples` test runs every example for each part that has an answer, so adding an example
+//! is a matter of adding these two files.
+
+use crate::answers::Answers;
+use crate::{Part, DAYS};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub day: u8,
+    pub name: String,
+    pub path: PathBuf,
+    pub answers: Answers,
+}
+
+impl Example {
+    /// A name for the test of `part` of this example, e.g. `day08/ghosts/part2`.
+    pub fn test_name(&self, part: Part) -> String {
+        format!("day{:02}/{}/part{part}", self.day, self.name)
+    }
+
+    /// The parts this example has answers for.
+    pub fn parts(&self) -> Vec<Part> {
+        Part::BOTH
+            .into_iter()
+            .filter(|&part| self.answers.get(part).is_some())
+            .collect()
+    }
+}
+
+/// The directory holding the examples of `day`, in the inputs directory `dir`.
+pub fn dir_for_day_in<P>(dir: P, day: u8) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    dir.as_ref().join(format!("day{day:02}")).join("examples")
+}
+
+/// All examples of all days in the inputs directory `dir`, ordered by day and name.
+pub fn discover<P>(dir: P) -> Result<Vec<Example>>
+where
+    P: AsRef<Path>,
+{
+    let mut examples = Vec::new();
+
+    for day in DAYS.iter() {
+        let dir = dir_for_day_in(&dir, day.number);
+
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e).with_context(|| format!("could not read {}", dir.display())),
+        };
+
+        let mut paths = Vec::new();
+
+        for entry in entries {
+            let path = entry?.path();
+
+            if path.is_file() && path.extension().is_none_or(|ext| ext != "answers") {
+                paths.push(path);
+            }
+        }
+
+        paths.sort();
+
+        for path in paths {
+            let answers = Answers::load(Answers::path_for(&path))?.unwrap_or_default();
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            examples.push(Example {
+                day: day.number,
+                name,
+                path,
+                answers,
+            });
+        }
+    }
+
+    Ok(examples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discover_examples() {
+        let examples = discover(Path::new(env!("CARGO_MANIFEST_DIR")).join("inputs")).unwrap();
+
+        let ghosts = examples
+            .iter()
+            .find(|e| e.day == 8 && e.name == "ghosts")
+            .unwrap();
+
+        assert_eq!(ghosts.parts(), [Part::Two]);
+        assert_eq!(ghosts.test_name(Part::Two), "day08/ghosts/part2");
+        assert!(examples.iter().all(|e| !e.name.ends_with(".answers")));
+    }
+}
@@ -0,0 +1,295 @@
+//! Random puzzle inputs, for stress testing and benchmarking.
+//!
+//! Each day has a generator in its module, e.g. [`crate::day11::generate`], which writes a valid
+//! input of a size set by its [`Knob`]s. Generators are deterministic: the same seed and knobs
+//! always give the same input.
+
+use crate::rng::Rng;
+use crate::{day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12};
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+
+/// The seed used when none is given.
+pub const DEFAULT_SEED: u64 = 2023;
+
+/// A setting of a generator, like the width of a grid.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Knob {
+    pub name: &'static str,
+    pub default: u64,
+    pub about: &'static str,
+}
+
+impl Knob {
+    pub const fn new(name: &'static str, default: u64, about: &'static str) -> Self {
+        Self {
+            name,
+            default,
+            about,
+        }
+    }
+}
+
+/// Values of knobs, by name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Knobs {
+    values: BTreeMap<String, u64>,
+}
+
+impl Knobs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set<S>(&mut self, name: S, value: u64)
+    where
+        S: Into<String>,
+    {
+        self.values.insert(name.into(), value);
+    }
+
+    /// The value of the knob `name`.
+    ///
+    /// Panics if it wasn't set, which can't happen inside a generator for one of its own knobs.
+    pub fn get(&self, name: &str) -> u64 {
+        match self.values.get(name) {
+            Some(&value) => value,
+            None => panic!("no knob named `{name}`"),
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+}
+
+/// An entry in the registry of generators, [`GENERATORS`].
+pub struct Generator {
+    pub day: u8,
+    pub knobs: &'static [Knob],
+    generate: fn(&mut Rng, &Knobs) -> Result<String>,
+}
+
+impl Generator {
+    /// Generate an input from `seed`, with the knobs in `knobs` and defaults for the others.
+    ///
+    /// Fails if `knobs` has a knob this generator doesn't know, or a value it can't work with.
+    pub fn generate(&self, seed: u64, knobs: &Knobs) -> Result<String> {
+        if let Some(name) = knobs.names().find(|name| !self.has_knob(name)) {
+            let known = self.knobs.iter().map(|k| k.name).collect::<Vec<_>>();
+            bail!(
+                "day {} has no knob `{name}`, only {}",
+                self.day,
+                known.join(", ")
+            );
+        }
+
+        let mut all = Knobs::new();
+
+        for knob in self.knobs {
+            let value = knobs.values.get(knob.name).copied();
+            all.set(knob.name, value.unwrap_or(knob.default));
+        }
+
+        (self.generate)(&mut Rng::new(seed), &all)
+    }
+
+    pub fn has_knob(&self, name: &str) -> bool {
+        self.knobs.iter().any(|knob| knob.name == name)
+    }
+}
+
+/// Generators for all days, in order.
+pub static GENERATORS: [Generator; 12] = [
+    Generator {
+        day: 1,
+        knobs: day01::KNOBS,
+        generate: day01::generate,
+    },
+    Generator {
+        day: 2,
+        knobs: day02::KNOBS,
+        generate: day02::generate,
+    },
+    Generator {
+        day: 3,
+        knobs: day03::KNOBS,
+        generate: day03::generate,
+    },
+    Generator {
+        day: 4,
+        knobs: day04::KNOBS,
+        generate: day04::generate,
+    },
+    Generator {
+        day: 5,
+        knobs: day05::KNOBS,
+        generate: day05::generate,
+    },
+    Generator {
+        day: 6,
+        knobs: day06::KNOBS,
+        generate: day06::generate,
+    },
+    Generator {
+        day: 7,
+        knobs: day07::KNOBS,
+        generate: day07::generate,
+    },
+    Generator {
+        day: 8,
+        knobs: day08::KNOBS,
+        generate: day08::generate,
+    },
+    Generator {
+        day: 9,
+        knobs: day09::KNOBS,
+        generate: day09::generate,
+    },
+    Generator {
+        day: 10,
+        knobs: day10::KNOBS,
+        generate: day10::generate,
+    },
+    Generator {
+        day: 11,
+        knobs: day11::KNOBS,
+        generate: day11::generate,
+    },
+    Generator {
+        day: 12,
+        knobs: day12::KNOBS,
+        generate: day12::generate,
+    },
+];
+
+/// Look up the generator of a day in [`GENERATORS`].
+pub fn generator(day: u8) -> Option<&'static Generator> {
+    GENERATORS.iter().find(|g| g.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Unsolved;
+    use crate::Part;
+
+    /// Knobs for inputs that are quick to solve in debug builds.
+    const SMALL: [(u8, &[(&str, u64)]); 12] = [
+        (1, &[("lines", 500)]),
+        (2, &[("games", 20)]),
+        (
+            3,
+            &[
+                ("width", 30),
+                ("height", 20),
+                ("numbers", 40),
+                ("symbols", 30),
+            ],
+        ),
+        (4, &[("cards", 30)]),
+        (5, &[("seeds", 3), ("ranges", 5)]),
+        (6, &[]),
+        (7, &[("hands", 50)]),
+        (8, &[("instructions", 7), ("ghosts", 3)]),
+        (9, &[("lines", 20)]),
+        (10, &[("width", 20), ("height", 15)]),
+        (11, &[("width", 20), ("height", 20), ("galaxies", 30)]),
+        (12, &[("records", 30), ("length", 12)]),
+    ];
+
+    #[test]
+    fn default_inputs_parse() {
+        for generator in GENERATORS.iter() {
+            let day = crate::day(generator.day).unwrap();
+            let input = generator.generate(DEFAULT_SEED, &Knobs::new()).unwrap();
+
+            if let Err(e) = day.parse(input.as_bytes()) {
+                panic!("day {}: {e:#}", day.number);
+            }
+        }
+    }
+
+    /// Seeds that small inputs are solved for, to catch inputs that only some seeds make.
+    const SEEDS: std::ops::Range<u64> = 0..100;
+
+    #[test]
+    fn small_inputs_solve() {
+        for (day, knobs) in SMALL {
+            let generator = generator(day).unwrap();
+            let day = crate::day(day).unwrap();
+
+            let mut settings = Knobs::new();
+
+            for &(name, value) in knobs {
+                settings.set(name, value);
+            }
+
+            for seed in SEEDS {
+                let input = generator.generate(seed, &settings).unwrap();
+                let puzzle = day.parse(input.as_bytes()).unwrap();
+
+                for part in Part::BOTH {
+                    // parts that aren't solved yet can't solve any input
+                    let answer = puzzle.solve(part);
+                    assert!(
+                        answer.as_ref().is_ok() || answer.as_ref().is_err_and(Unsolved::is),
+                        "day {} seed {seed}: {answer:?}",
+                        day.number
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn small_inputs_round_trip() {
+        for (day, knobs) in SMALL {
+            let generator = generator(day).unwrap();
+            let day = crate::day(day).unwrap();
+
+            let mut settings = Knobs::new();
+
+            for &(name, value) in knobs {
+                settings.set(name, value);
+            }
+
+            for seed in 0..5 {
+                let input = generator.generate(seed, &settings).unwrap();
+                let puzzle = day.parse(input.as_bytes()).unwrap();
+
+                let text = puzzle.to_string();
+                let parsed = day.parse(text.as_bytes()).unwrap();
+                assert_eq!(parsed.to_string(), text, "day {} seed {seed}", day.number);
+
+                for part in Part::BOTH {
+                    assert_eq!(
+                        parsed.solve(part).map_err(|e| e.to_string()),
+                        puzzle.solve(part).map_err(|e| e.to_string()),
+                        "day {} seed {seed} part {part}",
+                        day.number
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn knobs() {
+        let g = generator(11).unwrap();
+
+        let mut knobs = Knobs::new();
+        knobs.set("width", 7);
+        knobs.set("height", 3);
+        knobs.set("galaxies", 5);
+
+        let input = g.generate(1, &knobs).unwrap();
+        assert_eq!(input.lines().count(), 3);
+        assert!(input.lines().all(|line| line.len() == 7));
+        assert_eq!(input, g.generate(1, &knobs).unwrap());
+
+        knobs.set("depth", 2);
+        let e = g.generate(1, &knobs).unwrap_err();
+        assert!(e.to_string().starts_with("day 11 has no knob `depth`"));
+    }
+}
@@ -0,0 +1,236 @@
+//! Points and directions on a 2D grid.
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A point on the plane, or the vector between two of them.
+///
+/// Like in a [`Grid`](crate::grid::Grid), `y` grows downwards, so north is `(0, -1)`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Self = Self::new(0, 0);
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    /// The point one step from `self` in `direction`.
+    pub fn step(self, direction: Direction) -> Self {
+        self + direction.offset()
+    }
+
+    /// The point one step from `self` in `direction`, if it is within `0..width` and `0..height`.
+    pub fn step_within(self, direction: Direction, width: usize, height: usize) -> Option<Self> {
+        let p = self.step(direction);
+        p.is_within(width, height).then_some(p)
+    }
+
+    /// Whether `self` is within `0..width` and `0..height`.
+    pub fn is_within(self, width: usize, height: usize) -> bool {
+        let inside = |n: i64, len: usize| usize::try_from(n).is_ok_and(|n| n < len);
+        inside(self.x, width) && inside(self.y, height)
+    }
+
+    /// Distance to `other` moving only horizontally and vertically.
+    pub fn manhattan(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Distance to `other` when diagonal moves are allowed too.
+    pub fn chebyshev(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// Rotate 90 degrees clockwise around the origin.
+    pub fn rotate_right(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// Rotate 90 degrees counterclockwise around the origin.
+    pub fn rotate_left(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Self;
+
+    fn mul(self, n: i64) -> Self {
+        Self::new(self.x * n, self.y * n)
+    }
+}
+
+/// One of the four cardinal or four diagonal directions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    /// All directions, clockwise from north.
+    pub const ALL: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    /// The four cardinal directions, clockwise from north.
+    pub const CARDINAL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    /// The four diagonal directions, clockwise from north east.
+    pub const DIAGONAL: [Self; 4] = [
+        Self::NorthEast,
+        Self::SouthEast,
+        Self::SouthWest,
+        Self::NorthWest,
+    ];
+
+    /// The vector of a single step in this direction.
+    pub fn offset(self) -> Point {
+        use Direction::*;
+
+        match self {
+            North => Point::new(0, -1),
+            NorthEast => Point::new(1, -1),
+            East => Point::new(1, 0),
+            SouthEast => Point::new(1, 1),
+            South => Point::new(0, 1),
+            SouthWest => Point::new(-1, 1),
+            West => Point::new(-1, 0),
+            NorthWest => Point::new(-1, -1),
+        }
+    }
+
+    pub fn is_cardinal(self) -> bool {
+        (self as u8).is_multiple_of(2)
+    }
+
+    /// Turn clockwise by `eighths` eighths of a full turn, or counterclockwise if negative.
+    pub fn rotate(self, eighths: i32) -> Self {
+        Self::ALL[(self as i32 + eighths).rem_euclid(8) as usize]
+    }
+
+    /// The opposite direction.
+    pub fn inverse(self) -> Self {
+        self.rotate(4)
+    }
+
+    /// If you're walking in direction `self`, this gives the direction you'd go if you turned 90
+    /// degrees to your left.
+    pub fn left(self) -> Self {
+        self.rotate(-2)
+    }
+
+    /// If you're walking in direction `self`, this gives the direction you'd go if you turned 90
+    /// degrees to your right.
+    pub fn right(self) -> Self {
+        self.rotate(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directions() {
+        use Direction::*;
+
+        assert_eq!(North.inverse(), South);
+        assert_eq!(NorthWest.inverse(), SouthEast);
+        assert_eq!(North.left(), West);
+        assert_eq!(West.right(), North);
+        assert_eq!(NorthEast.rotate(-3), West);
+        assert_eq!(Direction::CARDINAL.map(Direction::is_cardinal), [true; 4]);
+        assert_eq!(Direction::DIAGONAL.map(Direction::is_cardinal), [false; 4]);
+
+        for dir in Direction::ALL {
+            assert_eq!(dir.inverse().offset(), -dir.offset());
+            assert_eq!(dir.right().offset(), dir.offset().rotate_right());
+            assert_eq!(dir.left().offset(), dir.offset().rotate_left());
+        }
+    }
+
+    #[test]
+    fn stepping() {
+        let p = Point::new(0, 2);
+
+        assert_eq!(p.step(Direction::SouthEast), Point::new(1, 3));
+        assert_eq!(p.step_within(Direction::West, 3, 3), None);
+        assert_eq!(p.step_within(Direction::South, 3, 3), None);
+        assert_eq!(
+            p.step_within(Direction::NorthEast, 3, 3),
+            Some(Point::new(1, 1))
+        );
+    }
+
+    #[test]
+    fn distances() {
+        let a = Point::new(1, 6);
+        let b = Point::new(5, 11);
+
+        assert_eq!(a.manhattan(b), 9);
+        assert_eq!(a.chebyshev(b), 5);
+        assert_eq!(b - a, Point::new(4, 5));
+        assert_eq!(a + (b - a) * 2, Point::new(9, 16));
+    }
+}
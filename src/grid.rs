@@ -0,0 +1,322 @@
+//! A rectangular grid of cells, for the puzzles whose input is a 2D map.
+
+use crate::error::ParseError;
+use crate::geometry::{Direction, Point};
+use anyhow::Result;
+use std::fmt;
+use std::io::BufRead;
+use std::ops::{Index, IndexMut};
+
+/// A rectangular grid, stored row by row.
+///
+/// Positions are [`Point`]s, with the origin in the top left corner and `y` growing downwards.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid of the given size, from its cells in row order.
+    ///
+    /// Panics if there are not exactly `width * height` cells.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), width * height, "wrong number of cells");
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// A grid of the given size with every cell set to `value`.
+    pub fn filled(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(width, height, vec![value; width * height])
+    }
+
+    /// Read a grid with one row per line, mapping each character to a cell with `f`.
+    ///
+    /// Errors returned by `f` only need to say what was expected; they are moved to where the
+    /// character is in the input. All lines must be as long as the first one.
+    pub fn parse<R, F>(reader: R, mut f: F) -> Result<Self>
+    where
+        R: BufRead,
+        F: FnMut(char) -> std::result::Result<T, ParseError>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let mut n = 0;
+
+            for (col, c) in line.char_indices() {
+                if width == Some(n) {
+                    let e = ParseError::at(&line, col, line.len(), "end of line");
+                    return Err(e.with_line(idx + 1).into());
+                }
+
+                let cell = f(c).map_err(|e| {
+                    ParseError::at(&line, col, col + c.len_utf8(), e.expected()).with_line(idx + 1)
+                })?;
+
+                cells.push(cell);
+                n += 1;
+            }
+
+            match width {
+                Some(width) if n < width => {
+                    let e = ParseError::at_end(&line, format!("{width} cells"));
+                    return Err(e.with_line(idx + 1).into());
+                }
+
+                Some(_) => {}
+                None => width = Some(n),
+            }
+
+            height += 1;
+        }
+
+        Ok(Self::new(width.unwrap_or(0), height, cells))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether `p` is inside the grid.
+    pub fn contains(&self, p: Point) -> bool {
+        p.is_within(self.width, self.height)
+    }
+
+    fn index_of(&self, p: Point) -> Option<usize> {
+        self.contains(p)
+            .then(|| p.x as usize + p.y as usize * self.width)
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.index_of(p).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.index_of(p).map(|idx| &mut self.cells[idx])
+    }
+
+    /// All cells with their positions, in row order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        let width = self.width;
+
+        self.cells.iter().enumerate().map(move |(idx, cell)| {
+            let p = Point::new((idx % width) as i64, (idx / width) as i64);
+            (p, cell)
+        })
+    }
+
+    /// The position one step from `p` in `direction`, if it is inside the grid.
+    pub fn step(&self, p: Point, direction: Direction) -> Option<Point> {
+        p.step_within(direction, self.width, self.height)
+    }
+
+    /// Positions of the up to 4 cells sharing an edge with `p`, clockwise from the top.
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::CARDINAL
+            .into_iter()
+            .filter_map(move |dir| self.step(p, dir))
+    }
+
+    /// Positions of the up to 8 cells sharing an edge or a corner with `p`, clockwise from the
+    /// top.
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |dir| self.step(p, dir))
+    }
+
+    /// The cells of row `y`. Panics if `y` is out of bounds.
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(y < self.height, "row {y} out of bounds");
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// The cells of column `x`, from top to bottom. Panics if `x` is out of bounds.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {x} out of bounds");
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// A grid of the same size, with `f` applied to every cell.
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    /// Mirror the grid along its main diagonal, turning rows into columns.
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        let cells = self.columns().flatten().cloned().collect();
+        Self::new(self.height, self.width, cells)
+    }
+
+    /// Rotate the grid 90 degrees clockwise.
+    pub fn rotate_right(&self) -> Self
+    where
+        T: Clone,
+    {
+        let cells = self
+            .columns()
+            .flat_map(|column| column.collect::<Vec<_>>().into_iter().rev())
+            .cloned()
+            .collect();
+
+        Self::new(self.height, self.width, cells)
+    }
+
+    /// Rotate the grid 90 degrees counterclockwise.
+    pub fn rotate_left(&self) -> Self
+    where
+        T: Clone,
+    {
+        let cells = (0..self.width)
+            .rev()
+            .flat_map(|x| self.column(x))
+            .cloned()
+            .collect();
+
+        Self::new(self.height, self.width, cells)
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get(p).unwrap_or_else(|| panic!("{p} out of bounds"))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("{p} out of bounds"))
+    }
+}
+
+/// Writes one line per row, with no separators between cells.
+impl<T> fmt::Display for Grid<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static EXAMPLE: &str = "abc\ndef\n";
+
+    fn chars(s: &str) -> Result<Grid<char>> {
+        Grid::parse(s.as_bytes(), Ok)
+    }
+
+    #[test]
+    fn parse() {
+        let grid = chars(EXAMPLE).unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(Point::new(2, 1)), Some(&'f'));
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.get(Point::new(0, 2)), None);
+        assert_eq!(grid.get(Point::new(-1, 0)), None);
+        assert_eq!(grid[Point::new(1, 0)], 'b');
+        assert_eq!(grid.to_string(), EXAMPLE);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let e = chars("abc\nde\n").unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!((e.line(), e.column()), (Some(2), 3));
+
+        let e = chars("abc\ndefg\n").unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!(e.found(), "g");
+
+        let e = Grid::parse("..\n.x\n".as_bytes(), |c| match c {
+            '.' => Ok(()),
+            _ => Err(ParseError::unexpected(c, "`.`")),
+        })
+        .unwrap_err();
+        let e = e.downcast::<ParseError>().unwrap();
+        assert_eq!((e.line(), e.column()), (Some(2), 2));
+        assert_eq!(e.expected(), "`.`");
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = chars(EXAMPLE).unwrap();
+
+        let n = grid.neighbours4(Point::ORIGIN).collect::<Vec<_>>();
+        assert_eq!(n, [Point::new(1, 0), Point::new(0, 1)]);
+
+        let n = grid.neighbours8(Point::new(1, 1)).collect::<Vec<_>>();
+        let expected = [(1, 0), (2, 0), (2, 1), (0, 1), (0, 0)].map(|(x, y)| Point::new(x, y));
+        assert_eq!(n, expected);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = chars(EXAMPLE).unwrap();
+
+        assert_eq!(grid.row(1), ['d', 'e', 'f']);
+        assert_eq!(grid.column(2).collect::<String>(), "cf");
+        assert_eq!(grid.columns().count(), 3);
+        assert_eq!(grid.rows().count(), 2);
+    }
+
+    #[test]
+    fn transform() {
+        let grid = chars(EXAMPLE).unwrap();
+
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_right().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_left().to_string(), "cf\nbe\nad\n");
+        assert_eq!(grid.rotate_right().rotate_left(), grid);
+        assert_eq!(
+            grid.map(|c| c.to_ascii_uppercase()).to_string(),
+            "ABC\nDEF\n"
+        );
+    }
+}
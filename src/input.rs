@@ -0,0 +1,128 @@
+//! Where puzzle inputs come from.
+//!
+//! By default, the input for day `n` is read from `inputs/day<n>/input`. The `inputs` directory
+//! can be moved with the `AOC_INPUTS` environment variable, and a single input can be given as a
+//! path, or as `-` for stdin.
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Environment variable that overrides [`DEFAULT_INPUTS_DIR`].
+pub const INPUTS_DIR_VAR: &str = "AOC_INPUTS";
+
+pub const DEFAULT_INPUTS_DIR: &str = "inputs";
+
+/// The directory holding the inputs of all days.
+pub fn inputs_dir() -> PathBuf {
+    match std::env::var_os(INPUTS_DIR_VAR) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(DEFAULT_INPUTS_DIR),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Stdin,
+    File(PathBuf),
+}
+
+impl Source {
+    /// The default input of `day` in [`inputs_dir`].
+    pub fn for_day(day: u8) -> Self {
+        Self::for_day_in(inputs_dir(), day)
+    }
+
+    /// The default input of `day` in `dir`.
+    pub fn for_day_in<P>(dir: P, day: u8) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self::File(dir.as_ref().join(format!("day{day:02}")).join("input"))
+    }
+
+    /// Read the whole input.
+    ///
+    /// Inputs are small enough to keep in memory, and reading them once means stdin can be used
+    /// for both parts.
+    pub fn read(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+
+        match self {
+            Self::Stdin => {
+                std::io::stdin()
+                    .lock()
+                    .read_to_end(&mut buf)
+                    .context("could not read stdin")?;
+            }
+
+            Self::File(path) => {
+                buf = std::fs::read(path)
+                    .with_context(|| format!("could not read {}", path.display()))?;
+            }
+        }
+
+        Ok(buf)
+    }
+
+    /// Open the input to read it bit by bit, for inputs too big to [`read`](Self::read).
+    pub fn open(&self) -> Result<Box<dyn BufRead>> {
+        match self {
+            Self::Stdin => Ok(Box::new(io::stdin().lock())),
+            Self::File(path) => {
+                let file = File::open(path)
+                    .with_context(|| format!("could not read {}", path.display()))?;
+                Ok(Box::new(BufReader::new(file)))
+            }
+        }
+    }
+}
+
+impl From<&str> for Source {
+    /// `-` means stdin, anything else is a path.
+    fn from(s: &str) -> Self {
+        match s {
+            "-" => Self::Stdin,
+            path => Self::File(PathBuf::from(path)),
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stdin => f.pad("-"),
+            Self::File(path) => f.pad(&path.display().to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day_paths() {
+        assert_eq!(
+            Source::for_day_in("inputs", 7),
+            Source::File(PathBuf::from("inputs/day07/input"))
+        );
+
+        assert_eq!(
+            Source::for_day_in("/tmp/alice", 12),
+            Source::File(PathBuf::from("/tmp/alice/day12/input"))
+        );
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!(Source::from("-"), Source::Stdin);
+        assert_eq!(
+            Source::from("my/input"),
+            Source::File(PathBuf::from("my/input"))
+        );
+        assert_eq!(Source::from("my/input").to_string(), "my/input");
+    }
+}
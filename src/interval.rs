@@ -0,0 +1,342 @@
+//! Half-open integer intervals, and sets of them.
+
+use crate::checked;
+use crate::error::OverflowError;
+use num::PrimInt;
+use std::fmt;
+
+/// The integers from `start` up to, but not including, `end`.
+///
+/// An interval with `start >= end` is empty. Empty intervals are all stored as `0..0`, so they
+/// compare equal.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Interval<T> {
+    start: T,
+    end: T,
+}
+
+impl<T: PrimInt> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        if start < end {
+            Self { start, end }
+        } else {
+            Self::empty()
+        }
+    }
+
+    /// The interval of `len` integers starting at `start`. Panics if that would overflow `T`.
+    pub fn with_len(start: T, len: T) -> Self {
+        let end = start.checked_add(&len).expect("interval end overflows");
+        Self::new(start, end)
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            start: T::zero(),
+            end: T::zero(),
+        }
+    }
+
+    pub fn start(&self) -> T {
+        self.start
+    }
+
+    pub fn end(&self) -> T {
+        self.end
+    }
+
+    pub fn len(&self) -> T {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn contains(&self, n: T) -> bool {
+        self.start <= n && n < self.end
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// The integers in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// The integers in `self` but not in `other`: up to two intervals, before and after `other`.
+    pub fn difference(&self, other: &Self) -> [Self; 2] {
+        if !self.overlaps(other) {
+            return [*self, Self::empty()];
+        }
+
+        [
+            Self::new(self.start, other.start),
+            Self::new(other.end, self.end),
+        ]
+    }
+
+    /// Move the interval so that what was at `from` ends up at `to`.
+    ///
+    /// Works for unsigned `T` in both directions. Fails if the result, or the distance between
+    /// `from` and `to`, overflows `T`.
+    pub fn shift(&self, from: T, to: T) -> Result<Self, OverflowError> {
+        if self.is_empty() {
+            return Ok(*self);
+        }
+
+        let moved = |n: T| {
+            if to >= from {
+                checked::add(n, checked::sub(to, from)?)
+            } else {
+                checked::sub(n, checked::sub(from, to)?)
+            }
+        };
+
+        Ok(Self::new(moved(self.start)?, moved(self.end)?))
+    }
+}
+
+impl<T: PrimInt> From<std::ops::Range<T>> for Interval<T> {
+    fn from(range: std::ops::Range<T>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Interval<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Interval<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}..{:?}", self.start, self.end)
+    }
+}
+
+/// A set of integers, stored as sorted intervals.
+///
+/// Intervals are coalesced as they are added: no two of them overlap or touch, and none are empty.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+
+    /// The intervals making up the set, in ascending order.
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The number of integers in the set.
+    pub fn len(&self) -> T {
+        self.intervals
+            .iter()
+            .fold(T::zero(), |len, interval| len + interval.len())
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(Interval::start)
+    }
+
+    pub fn contains(&self, n: T) -> bool {
+        // the first interval that doesn't end at or before `n` is the only one that could hold it
+        let idx = self.intervals.partition_point(|i| i.end <= n);
+        self.intervals.get(idx).is_some_and(|i| i.contains(n))
+    }
+
+    /// Add all integers in `interval`, merging it with the intervals it overlaps or touches.
+    pub fn insert(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+
+        // intervals before `first` end before `interval` starts, those from `last` on start after
+        // it ends; everything in between gets merged into it
+        let first = self.intervals.partition_point(|i| i.end < interval.start);
+        let last = self.intervals.partition_point(|i| i.start <= interval.end);
+
+        let mut merged = interval;
+
+        if first < last {
+            merged.start = merged.start.min(self.intervals[first].start);
+            merged.end = merged.end.max(self.intervals[last - 1].end);
+        }
+
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    /// The integers in `self`, `other` or both.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        union.extend(other.intervals.iter().copied());
+        union
+    }
+
+    /// The integers in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut a, mut b) = (0, 0);
+
+        while let (Some(x), Some(y)) = (self.intervals.get(a), other.intervals.get(b)) {
+            let both = x.intersection(y);
+
+            if !both.is_empty() {
+                intervals.push(both);
+            }
+
+            // whichever ends first can't overlap anything else in the other set
+            if x.end < y.end {
+                a += 1;
+            } else {
+                b += 1;
+            }
+        }
+
+        // the pieces are sorted and disjoint, and can't touch either, since both inputs were
+        // coalesced
+        Self { intervals }
+    }
+
+    /// The integers in `self` but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+
+        for interval in &self.intervals {
+            let mut rest = *interval;
+
+            for cut in other.intervals.iter().filter(|cut| cut.overlaps(interval)) {
+                let [before, after] = rest.difference(cut);
+
+                if !before.is_empty() {
+                    intervals.push(before);
+                }
+
+                rest = after;
+            }
+
+            if !rest.is_empty() {
+                intervals.push(rest);
+            }
+        }
+
+        Self { intervals }
+    }
+
+    /// Move every interval so that what was at `from` ends up at `to`, see [`Interval::shift`].
+    pub fn shift(&self, from: T, to: T) -> Result<Self, OverflowError> {
+        let intervals = self
+            .intervals
+            .iter()
+            .map(|i| i.shift(from, to))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { intervals })
+    }
+}
+
+impl<T: PrimInt> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PrimInt> Extend<Interval<T>> for IntervalSet<T> {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = Interval<T>>,
+    {
+        for interval in iter {
+            self.insert(interval);
+        }
+    }
+}
+
+impl<T: PrimInt> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Interval<T>>,
+    {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(&self.intervals).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(i32, i32)]) -> IntervalSet<i32> {
+        intervals
+            .iter()
+            .map(|&(start, end)| Interval::new(start, end))
+            .collect()
+    }
+
+    #[test]
+    fn interval() {
+        let a = Interval::new(2u8, 10);
+        let b = Interval::with_len(8, 5);
+
+        assert_eq!(a.len(), 8);
+        assert!(a.contains(2) && !a.contains(10));
+        assert_eq!(a.intersection(&b), Interval::new(8, 10));
+        assert_eq!(a.difference(&b), [Interval::new(2, 8), Interval::empty()]);
+        assert_eq!(b.difference(&a), [Interval::empty(), Interval::new(10, 13)]);
+        assert_eq!(Interval::new(5, 3), Interval::empty());
+        assert_eq!(a.shift(1, 6), Ok(Interval::new(7, 15)));
+        assert_eq!(a.shift(2, 0), Ok(Interval::new(0, 8)));
+        assert!(a.shift(0, 250).is_err());
+        assert!(a.shift(3, 0).is_err());
+        assert_eq!(format!("{a}"), "2..10");
+    }
+
+    #[test]
+    fn coalesce() {
+        let s = set(&[(10, 12), (1, 3), (3, 5), (20, 25), (11, 21), (-4, -2)]);
+
+        assert_eq!(s, set(&[(-4, -2), (1, 5), (10, 25)]));
+        assert_eq!(s.len(), 21);
+        assert_eq!(s.min(), Some(-4));
+        assert!(s.contains(24) && s.contains(1) && !s.contains(5) && !s.contains(0));
+        assert_eq!(format!("{s:?}"), "{-4..-2, 1..5, 10..25}");
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25), (28, 40)]);
+
+        assert_eq!(a.union(&b), set(&[(0, 40)]));
+        assert_eq!(a.intersection(&b), set(&[(5, 10), (20, 25), (28, 30)]));
+        assert_eq!(a.difference(&b), set(&[(0, 5), (25, 28)]));
+        assert_eq!(b.difference(&a), set(&[(10, 20), (30, 40)]));
+        assert_eq!(a.shift(0, -5), Ok(set(&[(-5, 5), (15, 25)])));
+
+        // the distance between far apart signed numbers doesn't fit, even if the result would
+        let e = a.shift(i32::MIN, i32::MAX).unwrap_err();
+        assert_eq!(e, OverflowError::new(crate::error::Op::Sub, "i32"));
+        assert!(set(&[(0, 10)]).shift(0, i32::MAX).is_err());
+        assert!(a.difference(&a).is_empty());
+    }
+}
@@ -1,9 +1,27 @@
 //! Advent of Code 2023
 //!
-//! Runnable programs live in `src/bin/`. Most solution logic lives in `src/day<n>.rs`, i.e. in the
-//! library part of this crate.
+//! The `aoc` program in `src/bin/aoc/` runs the puzzles, e.g. `aoc run 7 --part 2` or
+//! `aoc run all`. Most solution logic lives in `src/day<n>.rs`, i.e. in the library part of this
+//! crate. Each day implements [`Solution`] and is listed in [`DAYS`].
+//!
+//! - Inputs: [`input`], [`normalize`], [`examples`], [`generate`]
+//! - Parsing: [`parse`], [`error`], [`grid`], [`geometry`], [`interval`]
+//! - Solving: [`checked`], [`stream`], [`pool`], [`render`]
+//! - Results: [`answers`], [`cache`], [`bench`](mod@bench), [`memory`]
+//!
 //! Happy coding!
 
+use answers::Answer;
+use anyhow::Result;
+use normalize::{Layout, Normalize};
+use render::Canvas;
+use std::fmt;
+use std::io::BufRead;
+
+pub mod answers;
+pub mod bench;
+pub mod cache;
+pub mod checked;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -16,3 +34,232 @@ pub mod day09;
 pub mod day10;
 pub mod day11;
 pub mod day12;
+pub mod error;
+pub mod examples;
+pub mod generate;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod interval;
+pub mod memory;
+pub mod normalize;
+pub mod parse;
+pub mod pool;
+#[cfg(test)]
+mod prop;
+pub mod render;
+pub mod rng;
+pub mod stream;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const BOTH: [Self; 2] = [Self::One, Self::Two];
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n = match self {
+            Self::One => "1",
+            Self::Two => "2",
+        };
+
+        // pad, so that parts line up in tables
+        f.pad(n)
+    }
+}
+
+/// The solution to one day's puzzle.
+///
+/// The puzzle input is parsed once into `Self`, and both parts are then solved from that. Parts
+/// can return any type that makes an [`Answer`], like any integer type.
+///
+/// A part that isn't solved yet fails with [`Unsolved`](error::Unsolved), so that it is reported
+/// as unsolved rather than as failed.
+///
+/// `Display` writes the puzzle back out as puzzle input, which parses to the same puzzle again. It
+/// needn't be the same text: anything the parser ignores, like the order of a set, may change.
+pub trait Solution: Sized + fmt::Display {
+    type PartOne: Into<Answer>;
+    type PartTwo: Into<Answer>;
+
+    /// How the input is laid out, for [`Day`] to normalise it before parsing.
+    const LAYOUT: Layout = Layout::Text;
+
+    /// The version of the solver, which answers are [cached](cache) for. Change it when the
+    /// answers change, so that the old ones are solved again.
+    const VERSION: &'static str = "1";
+
+    fn parse<R>(reader: R) -> Result<Self>
+    where
+        R: BufRead;
+
+    fn part_one(&self) -> Result<Self::PartOne>;
+
+    fn part_two(&self) -> Result<Self::PartTwo>;
+
+    /// Draw the puzzle as the solver sees it, if this day can be drawn.
+    fn render(&self) -> Option<Canvas> {
+        None
+    }
+}
+
+/// A [`Solution`] that can also be found while reading the input line by line, see [`stream`].
+pub trait Streaming: Solution {
+    /// Solve both parts in a single pass over the lines of `reader`, without keeping them.
+    ///
+    /// The answers are the same as those from parsing the whole input and solving each part.
+    fn stream<R>(reader: R) -> (Result<Self::PartOne>, Result<Self::PartTwo>)
+    where
+        R: BufRead;
+}
+
+/// A parsed puzzle input of any day, see [`Day::parse`]. It displays as puzzle input, see
+/// [`Solution`].
+pub trait Puzzle: fmt::Display {
+    fn solve(&self, part: Part) -> Result<Answer>;
+
+    /// See [`Solution::render`].
+    fn render(&self) -> Option<Canvas>;
+}
+
+struct Erased<S>(S);
+
+impl<S> Puzzle for Erased<S>
+where
+    S: Solution,
+{
+    fn solve(&self, part: Part) -> Result<Answer> {
+        match part {
+            Part::One => Ok(self.0.part_one()?.into()),
+            Part::Two => Ok(self.0.part_two()?.into()),
+        }
+    }
+
+    fn render(&self) -> Option<Canvas> {
+        self.0.render()
+    }
+}
+
+impl<S> fmt::Display for Erased<S>
+where
+    S: Solution,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+fn parse_erased<S>(reader: &mut dyn BufRead) -> Result<Box<dyn Puzzle>>
+where
+    S: Solution + 'static,
+{
+    Ok(Box::new(Erased(S::parse(reader)?)))
+}
+
+fn stream_erased<S>(reader: &mut dyn BufRead) -> [Result<Answer>; 2]
+where
+    S: Streaming,
+{
+    let (one, two) = S::stream(reader);
+    [one.map(Into::into), two.map(Into::into)]
+}
+
+/// Solves both parts of a day while reading its input line by line, see [`Day::stream`].
+type StreamFn = fn(&mut dyn BufRead) -> [Result<Answer>; 2];
+
+/// An entry in the registry of solved days, [`DAYS`].
+pub struct Day {
+    pub number: u8,
+    version: &'static str,
+    layout: Layout,
+    parse: fn(&mut dyn BufRead) -> Result<Box<dyn Puzzle>>,
+    stream: Option<StreamFn>,
+}
+
+impl Day {
+    const fn new<S>(number: u8) -> Self
+    where
+        S: Solution + 'static,
+    {
+        Self {
+            number,
+            version: S::VERSION,
+            layout: S::LAYOUT,
+            parse: parse_erased::<S>,
+            stream: None,
+        }
+    }
+
+    const fn streaming<S>(number: u8) -> Self
+    where
+        S: Streaming + 'static,
+    {
+        Self {
+            number,
+            version: S::VERSION,
+            layout: S::LAYOUT,
+            parse: parse_erased::<S>,
+            stream: Some(stream_erased::<S>),
+        }
+    }
+
+    /// Parse an input of this day, after [normalising](normalize) it.
+    pub fn parse<R>(&self, reader: R) -> Result<Box<dyn Puzzle>>
+    where
+        R: BufRead,
+    {
+        (self.parse)(&mut Normalize::new(reader, self.layout))
+    }
+
+    /// See [`Solution::VERSION`].
+    pub fn version(&self) -> &'static str {
+        self.version
+    }
+
+    pub fn can_stream(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Solve both parts while reading `reader` line by line, if this day can, see [`Streaming`].
+    ///
+    /// The input is [normalised](normalize) as it is read.
+    pub fn stream<R>(&self, reader: R) -> Option<[Result<Answer>; 2]>
+    where
+        R: BufRead,
+    {
+        let mut reader = Normalize::new(reader, self.layout);
+        self.stream.map(|stream| stream(&mut reader))
+    }
+}
+
+impl fmt::Debug for Day {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Day").field("number", &self.number).finish()
+    }
+}
+
+/// All solved days, in order.
+pub static DAYS: [Day; 12] = [
+    Day::streaming::<day01::Document>(1),
+    Day::streaming::<day02::Games>(2),
+    Day::new::<day03::EngineSchematic>(3),
+    Day::streaming::<day04::Scratchcards>(4),
+    Day::new::<day05::Almanac>(5),
+    Day::new::<day06::Races>(6),
+    Day::new::<day07::Hands>(7),
+    Day::new::<day08::Map>(8),
+    Day::streaming::<day09::Report>(9),
+    Day::new::<day10::Graph>(10),
+    Day::new::<day11::Universe>(11),
+    Day::streaming::<day12::Records>(12),
+];
+
+/// Look up a day in [`DAYS`].
+pub fn day(number: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.number == number)
+}
@@ -0,0 +1,229 @@
+//! Counting heap allocations, to see how much memory parsing and each part use.
+//!
+//! With the `alloc-stats` feature, the global allocator counts the allocations of each thread, and
+//! [`measure`] reports those made by a closure. Without it, nothing is counted, and [`measure`]
+//! has nothing to report:
+//!
+//! ```text
+//! cargo run --release --features alloc-stats -- run all
+//! ```
+
+use std::fmt;
+
+/// Allocations made while running some code, see [`measure`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Number of allocations, counting each reallocation as one.
+    pub allocations: u64,
+
+    /// Bytes allocated in total, however much of it was freed again.
+    pub bytes: u64,
+
+    /// The most the heap grew by at any point.
+    pub peak: u64,
+}
+
+/// Whether allocations are counted, i.e. if the `alloc-stats` feature is enabled.
+pub const fn enabled() -> bool {
+    cfg!(feature = "alloc-stats")
+}
+
+/// Run `f`, and count the allocations it makes on this thread if [`enabled`].
+pub fn measure<T, F>(f: F) -> (T, Option<AllocStats>)
+where
+    F: FnOnce() -> T,
+{
+    #[cfg(feature = "alloc-stats")]
+    {
+        let (value, stats) = counting::measure(f);
+        (value, Some(stats))
+    }
+
+    #[cfg(not(feature = "alloc-stats"))]
+    {
+        (f(), None)
+    }
+}
+
+/// A number of bytes, displayed in binary units, e.g. `1.5 KiB`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Bytes(pub u64);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+        if self.0 < 1024 {
+            return f.pad(&format!("{} B", self.0));
+        }
+
+        let mut size = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+
+        while size >= 1024.0 && unit + 1 < UNITS.len() {
+            size /= 1024.0;
+            unit += 1;
+        }
+
+        f.pad(&format!("{size:.1} {}", UNITS[unit]))
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+mod counting {
+    use super::AllocStats;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    #[derive(Debug, Copy, Clone)]
+    struct Counts {
+        allocations: u64,
+        bytes: u64,
+
+        /// Bytes allocated and not yet freed. Memory freed by another thread than the one that
+        /// allocated it can make this negative.
+        current: i64,
+
+        /// The highest `current` has been since it was last reset.
+        peak: i64,
+    }
+
+    thread_local! {
+        // const, and without drop glue, so that the allocator can always use it
+        static COUNTS: Cell<Counts> = const {
+            Cell::new(Counts {
+                allocations: 0,
+                bytes: 0,
+                current: 0,
+                peak: 0,
+            })
+        };
+    }
+
+    fn update<F>(f: F)
+    where
+        F: FnOnce(&mut Counts),
+    {
+        let _ = COUNTS.try_with(|counts| {
+            let mut c = counts.get();
+            f(&mut c);
+            c.peak = c.peak.max(c.current);
+            counts.set(c);
+        });
+    }
+
+    fn allocated(size: usize) {
+        update(|c| {
+            c.allocations += 1;
+            c.bytes += size as u64;
+            c.current += size as i64;
+        });
+    }
+
+    /// The system allocator, counting what it does for each thread.
+    struct Counting;
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+
+            if !ptr.is_null() {
+                allocated(layout.size());
+            }
+
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+
+            if !ptr.is_null() {
+                allocated(layout.size());
+            }
+
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            update(|c| c.current -= layout.size() as i64);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new = System.realloc(ptr, layout, new_size);
+
+            if !new.is_null() {
+                allocated(new_size);
+                update(|c| c.current -= layout.size() as i64);
+            }
+
+            new
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: Counting = Counting;
+
+    pub fn measure<T, F>(f: F) -> (T, AllocStats)
+    where
+        F: FnOnce() -> T,
+    {
+        let before = COUNTS.with(Cell::get);
+
+        // track the peak from here on, but keep the one from before for any outer measurement
+        update(|c| c.peak = c.current);
+        let value = f();
+        let after = COUNTS.with(Cell::get);
+        update(|c| c.peak = c.peak.max(before.peak));
+
+        let stats = AllocStats {
+            allocations: after.allocations - before.allocations,
+            bytes: after.bytes - before.bytes,
+            peak: (after.peak - before.current).max(0) as u64,
+        };
+
+        (value, stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes() {
+        assert_eq!(Bytes(0).to_string(), "0 B");
+        assert_eq!(Bytes(1023).to_string(), "1023 B");
+        assert_eq!(Bytes(1536).to_string(), "1.5 KiB");
+        assert_eq!(Bytes(3 << 30).to_string(), "3.0 GiB");
+        assert_eq!(format!("{:>9}", Bytes(2048)), "  2.0 KiB");
+    }
+
+    #[test]
+    fn measure_counts_if_enabled() {
+        let (v, stats) = measure(|| {
+            let big = vec![0u8; 4096];
+            drop(big);
+
+            let (_, inner) = measure(|| vec![1u64; 8]);
+            assert_eq!(inner.is_some(), enabled());
+
+            (0..10u64).map(|n| vec![n; 10]).collect::<Vec<_>>()
+        });
+
+        assert_eq!(v.len(), 10);
+
+        if !enabled() {
+            assert_eq!(stats, None);
+            return;
+        }
+
+        let stats = stats.unwrap();
+
+        // the freed 4 KiB still count towards the peak and the bytes allocated
+        assert!(stats.allocations >= 13);
+        assert!(stats.bytes >= 4096 + 64 + 10 * 80);
+        assert!(stats.peak >= 4096);
+        assert!(stats.peak < stats.bytes);
+    }
+}
@@ -0,0 +1,247 @@
+//! Cleaning up puzzle inputs before they are parsed.
+//!
+//! Inputs that went through other editors or machines come with all sorts of noise that parsers
+//! shouldn't have to care about. Every day in [`DAYS`](crate::DAYS) reads its input through
+//! [`Normalize`], which
+//!
+//! - strips a byte order mark,
+//! - ends lines at `\n`, `\r\n` or a lone `\r`,
+//! - drops trailing whitespace, and blank lines at the end of the input,
+//! - collapses runs of spaces and tabs into a single space, for inputs that are [`Layout::Text`],
+//! - and rejects ragged grids, for inputs that are [`Layout::Grid`].
+//!
+//! It works line by line, so it can clean up inputs too big to keep in memory as they are
+//! [streamed](crate::stream). Lines keep their numbers, so errors still point at the right line.
+
+use crate::error::ParseError;
+use std::io::{self, BufRead, Read};
+
+/// How a puzzle input is laid out, which decides how it is normalised.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Layout {
+    /// Words and numbers, where only line breaks and whether there is whitespace between them
+    /// matter.
+    Text,
+    /// A map with one character per cell, where all rows must be as wide.
+    Grid,
+}
+
+const BOM: char = '\u{feff}';
+
+/// A reader of a normalised puzzle input, see the [module docs](self).
+///
+/// Errors, like those for a ragged grid or invalid UTF-8, are [`io::Error`]s of kind
+/// [`InvalidData`](io::ErrorKind::InvalidData), so that parsers get them reading lines.
+#[derive(Debug)]
+pub struct Normalize<R> {
+    inner: R,
+    layout: Layout,
+    /// Number of the last line read.
+    line_no: usize,
+    /// Blank lines held back until there is another line, as they may be the end of the input.
+    blank: usize,
+    /// Width of the grid, once its first row is read.
+    width: Option<(usize, usize)>,
+    /// Normalised lines, ready to be read from `pos` on.
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl<R> Normalize<R>
+where
+    R: BufRead,
+{
+    pub fn new(inner: R, layout: Layout) -> Self {
+        Self {
+            inner,
+            layout,
+            line_no: 0,
+            blank: 0,
+            width: None,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Read physical lines from `inner` until there is at least one normalised line in `buf`, or
+    /// the input ends.
+    fn refill(&mut self) -> io::Result<()> {
+        self.buf.clear();
+        self.pos = 0;
+
+        let mut raw = Vec::new();
+
+        while self.buf.is_empty() {
+            raw.clear();
+
+            if self.inner.read_until(b'\n', &mut raw)? == 0 {
+                // any blank lines held back were the end of the input
+                return Ok(());
+            }
+
+            let text = std::str::from_utf8(&raw).map_err(invalid)?;
+
+            let text = match text.strip_suffix('\n') {
+                Some(text) => text.strip_suffix('\r').unwrap_or(text),
+                None => text,
+            };
+
+            let text = match self.line_no {
+                0 => text.strip_prefix(BOM).unwrap_or(text),
+                _ => text,
+            };
+
+            // what is left of any `\r` are line breaks of their own
+            for line in text.split('\r') {
+                self.push(line)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Normalise the next line, and add it to `buf`.
+    fn push(&mut self, line: &str) -> io::Result<()> {
+        self.line_no += 1;
+
+        let line = match self.layout {
+            Layout::Text => line.split_whitespace().collect::<Vec<_>>().join(" "),
+            Layout::Grid => line.trim_end().to_owned(),
+        };
+
+        if line.is_empty() {
+            self.blank += 1;
+            return Ok(());
+        }
+
+        if self.layout == Layout::Grid {
+            self.check_width(&line)?;
+        }
+
+        for _ in 0..std::mem::take(&mut self.blank) {
+            self.buf.push(b'\n');
+        }
+
+        self.buf.extend_from_slice(line.as_bytes());
+        self.buf.push(b'\n');
+
+        Ok(())
+    }
+
+    /// Check that a row of a grid is as wide as its first row.
+    fn check_width(&mut self, line: &str) -> io::Result<()> {
+        let n = line.chars().count();
+
+        let Some((width, first)) = self.width else {
+            self.width = Some((n, self.line_no));
+            return Ok(());
+        };
+
+        let expected = format!("{width} cells, as many as on line {first}");
+
+        let e = match line.char_indices().nth(width) {
+            Some((col, _)) => ParseError::at(line, col, line.len(), expected),
+            None if n < width => ParseError::at_end(line, expected),
+            None => return Ok(()),
+        };
+
+        Err(invalid(e.with_line(self.line_no)))
+    }
+}
+
+fn invalid<E>(e: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+impl<R> Read for Normalize<R>
+where
+    R: BufRead,
+{
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let buf = self.fill_buf()?;
+        let n = buf.len().min(out.len());
+
+        out[..n].copy_from_slice(&buf[..n]);
+        self.consume(n);
+
+        Ok(n)
+    }
+}
+
+impl<R> BufRead for Normalize<R>
+where
+    R: BufRead,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos == self.buf.len() {
+            self.refill()?;
+        }
+
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.buf.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(input: &str, layout: Layout) -> io::Result<String> {
+        let mut normalized = String::new();
+        Normalize::new(input.as_bytes(), layout).read_to_string(&mut normalized)?;
+        Ok(normalized)
+    }
+
+    #[test]
+    fn text() {
+        let input = "\u{feff}seeds: 79\t 14  \r\n\r\nseed-to-soil\tmap:\r 50 98 2\r\n\n \t\n";
+        let expected = "seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n";
+        assert_eq!(normalize(input, Layout::Text).unwrap(), expected);
+
+        assert_eq!(normalize("", Layout::Text).unwrap(), "");
+        assert_eq!(normalize("a", Layout::Text).unwrap(), "a\n");
+        assert_eq!(normalize("\n\na\n", Layout::Text).unwrap(), "\n\na\n");
+    }
+
+    #[test]
+    fn grid() {
+        let input = "\u{feff}.#. \r\n #.\t\r\n...\r\n\r\n";
+        assert_eq!(normalize(input, Layout::Grid).unwrap(), ".#.\n #.\n...\n");
+
+        let e = normalize("...\n..\n...\n", Layout::Grid).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+
+        let e = e.into_inner().unwrap().downcast::<ParseError>().unwrap();
+        assert_eq!(e.line(), Some(2));
+        assert_eq!(e.expected(), "3 cells, as many as on line 1");
+
+        let e = normalize("\n..\n...\n", Layout::Grid).unwrap_err();
+        let e = e.into_inner().unwrap().downcast::<ParseError>().unwrap();
+        assert_eq!((e.line(), e.column(), e.found()), (Some(3), 3, "."));
+    }
+
+    #[test]
+    fn line_breaks() {
+        let lines = Normalize::new("a\r\n\r\nb\rc\n".as_bytes(), Layout::Text)
+            .lines()
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(lines, ["a", "", "b", "c"]);
+
+        let text = normalize("line 1\nline 2: \u{feff}", Layout::Text).unwrap();
+        assert_eq!(
+            text, "line 1\nline 2: \u{feff}\n",
+            "only a leading BOM is stripped"
+        );
+
+        let mut invalid = String::new();
+        let e = Normalize::new(&b"a\n\xff\n"[..], Layout::Text).read_to_string(&mut invalid);
+        assert_eq!(e.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
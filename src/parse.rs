@@ -0,0 +1,296 @@
+//! Building blocks for parsing single lines of puzzle input.
+//!
+//! Most inputs are lines like `Card 1: 41 48 83 | 83 86  6`: some fixed text, integers and words
+//! separated by any amount of whitespace, and a delimiter or two. [`Scanner`] reads such a line
+//! from left to right, and points its errors at the part of the line that was wrong:
+//!
+//! ```
+//! use aoc_2023::parse::Scanner;
+//!
+//! let mut s = Scanner::new("Card 1: 41 48 83 | 83 86  6");
+//!
+//! s.tag("Card")?;
+//! let id = s.integer::<u32>("a card ID")?;
+//! s.tag(":")?;
+//! let winning = s.until("|")?.integers::<u32>()?;
+//! let have = s.integers::<u32>()?;
+//!
+//! assert_eq!((id, winning, have), (1, vec![41, 48, 83], vec![83, 86, 6]));
+//! # Ok::<(), aoc_2023::error::ParseError>(())
+//! ```
+
+use crate::error::ParseError;
+use std::str::FromStr;
+
+/// A primitive integer type.
+pub trait Integer: FromStr + Copy {
+    /// Whether the type can hold negative numbers, i.e. whether a leading `-` belongs to it.
+    const SIGNED: bool;
+}
+
+macro_rules! integer {
+    ($signed:literal: $($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const SIGNED: bool = $signed;
+            }
+        )*
+    };
+}
+
+integer!(false: u8, u16, u32, u64, u128, usize);
+integer!(true: i8, i16, i32, i64, i128, isize);
+
+/// Length of the integer at the start of `s`, if there is one.
+fn integer_len<T: Integer>(s: &str) -> Option<usize> {
+    let sign = usize::from(T::SIGNED && s.starts_with('-'));
+
+    let digits = s[sign..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(s.len() - sign);
+
+    (digits > 0).then_some(sign + digits)
+}
+
+/// All integers in `line`, ignoring whatever is between them.
+///
+/// A `-` directly in front of a number makes it negative if `T` is signed.
+pub fn extract_integers<T: Integer>(line: &str) -> Result<Vec<T>, ParseError> {
+    let mut integers = Vec::new();
+    let mut rest = line;
+
+    while !rest.is_empty() {
+        match integer_len::<T>(rest) {
+            Some(len) => {
+                let (number, tail) = rest.split_at(len);
+                integers.push(parse_integer(line, number)?);
+                rest = tail;
+            }
+
+            None => {
+                let skip = rest.chars().next().map_or(0, char::len_utf8);
+                rest = &rest[skip..];
+            }
+        }
+    }
+
+    Ok(integers)
+}
+
+/// Parse `number`, which is a slice of `line` made up of a sign and digits only.
+fn parse_integer<T: Integer>(line: &str, number: &str) -> Result<T, ParseError> {
+    // the text is well-formed, so this can only fail if the number doesn't fit
+    number
+        .parse()
+        .map_err(|_| ParseError::new(line, number, "a smaller number"))
+}
+
+/// Reads a line from left to right.
+///
+/// Everything except [`Scanner::until`] skips whitespace before what it reads, so any amount of
+/// whitespace may separate tokens. A token is a word, an integer, or any other single character.
+#[derive(Debug, Copy, Clone)]
+pub struct Scanner<'a> {
+    line: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(line: &'a str) -> Self {
+        Self { line, rest: line }
+    }
+
+    /// The whole line, including what was already read.
+    pub fn line(&self) -> &'a str {
+        self.line
+    }
+
+    /// What has not been read yet.
+    pub fn rest(&self) -> &'a str {
+        self.rest
+    }
+
+    /// Whether there is nothing left but whitespace.
+    pub fn is_empty(&self) -> bool {
+        self.rest.trim_start().is_empty()
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    /// The next token, without reading it.
+    fn peek_token(&self) -> &'a str {
+        let rest = self.rest.trim_start();
+
+        let len = if rest.starts_with(|c: char| c.is_alphanumeric()) {
+            rest.find(|c: char| !c.is_alphanumeric())
+                .unwrap_or(rest.len())
+        } else {
+            rest.chars().next().map_or(0, char::len_utf8)
+        };
+
+        &rest[..len]
+    }
+
+    /// An error saying the next token should have been `expected`.
+    pub fn error<S>(&self, expected: S) -> ParseError
+    where
+        S: Into<String>,
+    {
+        match self.peek_token() {
+            "" => ParseError::at_end(self.line, expected),
+            token => ParseError::new(self.line, token, expected),
+        }
+    }
+
+    /// Read `tag` if the rest starts with it.
+    pub fn eat(&mut self, tag: &str) -> bool {
+        match self.rest.trim_start().strip_prefix(tag) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+
+            None => false,
+        }
+    }
+
+    /// Read `tag`, which must come next.
+    pub fn tag(&mut self, tag: &str) -> Result<(), ParseError> {
+        if self.eat(tag) {
+            Ok(())
+        } else {
+            Err(self.error(format!("`{tag}`")))
+        }
+    }
+
+    /// Read a word, i.e. a run of letters and digits.
+    pub fn word(&mut self, expected: &str) -> Result<&'a str, ParseError> {
+        match self.peek_token() {
+            word if word.starts_with(|c: char| c.is_alphanumeric()) => {
+                self.skip_whitespace();
+                self.rest = &self.rest[word.len()..];
+                Ok(word)
+            }
+
+            _ => Err(self.error(expected)),
+        }
+    }
+
+    /// Read an integer, which must not run into a word, e.g. the `1` in `1x5`.
+    pub fn integer<T: Integer>(&mut self, expected: &str) -> Result<T, ParseError> {
+        let rest = self.rest.trim_start();
+
+        let len = integer_len::<T>(rest)
+            .filter(|&len| !rest[len..].starts_with(|c: char| c.is_alphanumeric()))
+            .ok_or_else(|| {
+                // point at the whole word rather than at whatever made it an invalid number
+                let word = rest.split(|c: char| c.is_whitespace()).next();
+
+                match word {
+                    Some(word) if !word.is_empty() => ParseError::new(self.line, word, expected),
+                    _ => ParseError::at_end(self.line, expected),
+                }
+            })?;
+
+        let (number, rest) = rest.split_at(len);
+        let n = parse_integer(self.line, number)?;
+
+        self.rest = rest;
+        Ok(n)
+    }
+
+    /// Read integers until the end of the line.
+    pub fn integers<T: Integer>(&mut self) -> Result<Vec<T>, ParseError> {
+        let mut integers = Vec::new();
+
+        while !self.is_empty() {
+            integers.push(self.integer("a number")?);
+        }
+
+        Ok(integers)
+    }
+
+    /// Split off everything up to the next `delimiter`, and skip the delimiter.
+    ///
+    /// The returned scanner reports its errors on the whole line, like this one does.
+    pub fn until(&mut self, delimiter: &str) -> Result<Self, ParseError> {
+        let (head, rest) = self
+            .rest
+            .split_once(delimiter)
+            .ok_or_else(|| ParseError::at_end(self.line, format!("`{delimiter}`")))?;
+
+        self.rest = rest;
+
+        Ok(Self {
+            line: self.line,
+            rest: head,
+        })
+    }
+
+    /// Check that nothing but whitespace is left.
+    pub fn end(&self) -> Result<(), ParseError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("end of line"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers() {
+        let mut s = Scanner::new("  -12 7\t\t-0 18446744073709551615");
+
+        assert_eq!(s.integer::<i8>("a number"), Ok(-12));
+        assert_eq!(s.integers::<i128>().unwrap(), [7, 0, u64::MAX as i128]);
+
+        let e = Scanner::new("-12").integer::<u32>("a number").unwrap_err();
+        assert_eq!(e.found(), "-12");
+
+        let e = Scanner::new("1 256").integers::<u8>().unwrap_err();
+        assert_eq!((e.found(), e.expected()), ("256", "a smaller number"));
+
+        let e = Scanner::new("7 1x5").integers::<u8>().unwrap_err();
+        assert_eq!((e.found(), e.expected()), ("1x5", "a number"));
+    }
+
+    #[test]
+    fn extract() {
+        let line = "x=-3, y=10..0 and 5-2";
+
+        assert_eq!(extract_integers::<i32>(line), Ok(vec![-3, 10, 0, 5, -2]));
+        assert_eq!(extract_integers::<u32>(line), Ok(vec![3, 10, 0, 5, 2]));
+
+        let e = extract_integers::<u8>("1 2 300").unwrap_err();
+        assert_eq!(e.column(), 5);
+    }
+
+    #[test]
+    fn tags_and_words() {
+        let mut s = Scanner::new("Game 12: 3 blue; 1 red");
+
+        s.tag("Game").unwrap();
+        assert_eq!(s.integer("an ID"), Ok(12u32));
+        s.tag(":").unwrap();
+
+        let mut first = s.until(";").unwrap();
+        assert_eq!(first.integer("a number"), Ok(3u32));
+        assert_eq!(first.word("a color"), Ok("blue"));
+        assert!(first.end().is_ok());
+
+        assert!(!s.eat(","));
+        assert_eq!(s.tag("red").unwrap_err().found(), "1");
+        assert_eq!(s.integer("a number"), Ok(1u32));
+        assert_eq!(s.end().unwrap_err().found(), "red");
+        assert_eq!(s.word("a color"), Ok("red"));
+
+        let e = s.tag(")").unwrap_err();
+        assert_eq!((e.column(), e.found()), (23, ""));
+    }
+}
@@ -0,0 +1,113 @@
+//! A small pool of worker threads, for solving several days at once.
+
+use std::any::Any;
+use std::num::NonZeroUsize;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
+/// The number of workers to use by default: one per available core.
+pub fn default_workers() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Apply `f` to each of `items` on up to `workers` threads.
+///
+/// A panic in `f` only fails the item it panicked for, and is returned as an error in its place.
+/// Results are in the order of `items`, whatever order they are finished in.
+pub fn map<T, R, F>(items: Vec<T>, workers: usize, f: F) -> Vec<thread::Result<R>>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let n = items.len();
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let (tx, rx) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..workers.clamp(1, n.max(1)) {
+            let tx = tx.clone();
+            let (queue, f) = (&queue, &f);
+
+            scope.spawn(move || loop {
+                // `f` runs outside of the lock, so a panic can't poison it
+                let next = queue.lock().unwrap().next();
+
+                let Some((idx, item)) = next else {
+                    break;
+                };
+
+                let result = catch_unwind(AssertUnwindSafe(|| f(item)));
+
+                if tx.send((idx, result)).is_err() {
+                    break;
+                }
+            });
+        }
+    });
+
+    drop(tx);
+
+    let mut results = (0..n).map(|_| None).collect::<Vec<_>>();
+
+    for (idx, result) in rx {
+        results[idx] = Some(result);
+    }
+
+    results
+        .into_iter()
+        .map(|result| result.expect("every item is processed"))
+        .collect()
+}
+
+/// The message a panic was started with, if it was given one.
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "(no message)"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn keeps_order() {
+        let items = (0..20).collect::<Vec<u64>>();
+
+        // later items finish first
+        let results = map(items, 4, |n| {
+            thread::sleep(Duration::from_millis(20 - n));
+            n * n
+        });
+
+        let results = results.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+        assert_eq!(results, (0..20).map(|n| n * n).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn catches_panics() {
+        let results = map(vec![1, 0, 2], 2, |n| {
+            if n == 0 {
+                panic!("no zeros please");
+            }
+
+            10 / n
+        });
+
+        assert_eq!(*results[0].as_ref().unwrap(), 10);
+        assert_eq!(
+            panic_message(&**results[1].as_ref().unwrap_err()),
+            "no zeros please"
+        );
+        assert_eq!(*results[2].as_ref().unwrap(), 5);
+
+        assert!(map(Vec::<u8>::new(), 0, |n| n).is_empty());
+    }
+}
@@ -0,0 +1,214 @@
+//! Property-based testing: check that something holds for many random inputs, and shrink any
+//! input it fails for to a minimal one.
+//!
+//! ```ignore
+//! prop::check(|rng| rng.range(0..100), |&n| n * 2 >= n);
+//! ```
+//!
+//! The number of cases and the seed can be changed with `AOC_PROP_CASES` and `AOC_PROP_SEED`. A
+//! failure reports the seed of the failing case, so it can be reproduced.
+
+use crate::rng::Rng;
+use std::fmt::Debug;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+const DEFAULT_CASES: u64 = 2000;
+const DEFAULT_SEED: u64 = 2023;
+
+/// Upper bound on shrinking steps, in case shrinking doesn't converge.
+const MAX_SHRINKS: usize = 10_000;
+
+/// Values that can be made simpler, to find a minimal failing input.
+pub trait Shrink: Clone + Debug {
+    /// Simpler variants of `self`, most aggressive first.
+    fn shrink(&self) -> Vec<Self>;
+}
+
+macro_rules! shrink_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Shrink for $t {
+                fn shrink(&self) -> Vec<Self> {
+                    let n = *self;
+                    let mut simpler = vec![0, n / 2, n.saturating_sub(1)];
+
+                    simpler.dedup();
+                    simpler.retain(|&s| s != n);
+                    simpler
+                }
+            }
+        )*
+    };
+}
+
+shrink_unsigned!(u8, u16, u32, u64, usize);
+
+impl Shrink for bool {
+    fn shrink(&self) -> Vec<Self> {
+        if *self {
+            vec![false]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl<T: Shrink> Shrink for Vec<T> {
+    fn shrink(&self) -> Vec<Self> {
+        let mut simpler = Vec::new();
+        let n = self.len();
+
+        if n == 0 {
+            return simpler;
+        }
+
+        simpler.push(Vec::new());
+
+        if n > 1 {
+            simpler.push(self[..n / 2].to_vec());
+            simpler.push(self[n / 2..].to_vec());
+        }
+
+        for i in 0..n {
+            let mut removed = self.clone();
+            removed.remove(i);
+            simpler.push(removed);
+        }
+
+        for (i, item) in self.iter().enumerate() {
+            for s in item.shrink() {
+                let mut shrunk = self.clone();
+                shrunk[i] = s;
+                simpler.push(shrunk);
+            }
+        }
+
+        simpler
+    }
+}
+
+impl<A: Shrink, B: Shrink> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<Self> {
+        let (a, b) = self;
+
+        let mut simpler = a
+            .shrink()
+            .into_iter()
+            .map(|a| (a, b.clone()))
+            .collect::<Vec<_>>();
+
+        simpler.extend(b.shrink().into_iter().map(|b| (a.clone(), b)));
+        simpler
+    }
+}
+
+impl<A: Shrink, B: Shrink, C: Shrink> Shrink for (A, B, C) {
+    fn shrink(&self) -> Vec<Self> {
+        let (a, b, c) = self;
+
+        ((a.clone(), b.clone()), c.clone())
+            .shrink()
+            .into_iter()
+            .map(|((a, b), c)| (a, b, c))
+            .collect()
+    }
+}
+
+fn env_or(var: &str, default: u64) -> u64 {
+    std::env::var(var)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Whether `property` holds for `input`, counting a panic as a failure.
+fn holds<T, P>(property: &P, input: &T) -> bool
+where
+    P: Fn(&T) -> bool,
+{
+    catch_unwind(AssertUnwindSafe(|| property(input))).unwrap_or(false)
+}
+
+/// Check that `property` holds for inputs made by `generate`.
+///
+/// Panics with a minimal failing input if it doesn't.
+pub fn check<T, G, P>(mut generate: G, property: P)
+where
+    T: Shrink,
+    G: FnMut(&mut Rng) -> T,
+    P: Fn(&T) -> bool,
+{
+    let cases = env_or("AOC_PROP_CASES", DEFAULT_CASES);
+    let seed = env_or("AOC_PROP_SEED", DEFAULT_SEED);
+
+    for case in 0..cases {
+        let case_seed = seed.wrapping_add(case);
+        let input = generate(&mut Rng::new(case_seed));
+
+        if holds(&property, &input) {
+            continue;
+        }
+
+        let (minimal, shrinks) = shrink(input.clone(), &property);
+
+        panic!(
+            "property failed for case {case} (AOC_PROP_SEED={case_seed} AOC_PROP_CASES=1)\n\
+             input:   {input:?}\n\
+             minimal: {minimal:?} (after {shrinks} shrinks)"
+        );
+    }
+}
+
+/// Shrink `input`, for which `property` fails, for as long as a simpler input fails too.
+fn shrink<T, P>(mut input: T, property: &P) -> (T, usize)
+where
+    T: Shrink,
+    P: Fn(&T) -> bool,
+{
+    let mut shrinks = 0;
+
+    'outer: while shrinks < MAX_SHRINKS {
+        for simpler in input.shrink() {
+            if !holds(property, &simpler) {
+                input = simpler;
+                shrinks += 1;
+                continue 'outer;
+            }
+        }
+
+        break;
+    }
+
+    (input, shrinks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes() {
+        check(|rng| rng.range(0..1000), |&n| n / 2 <= n);
+    }
+
+    #[test]
+    fn shrinks_to_minimal_input() {
+        // fails for any vector with an element of at least 10
+        let property = |v: &Vec<u64>| v.iter().all(|&n| n < 10);
+
+        let input = vec![3, 40, 7, 12];
+        assert!(!property(&input));
+
+        let (minimal, _) = shrink(input, &property);
+        assert_eq!(minimal, [10]);
+    }
+
+    #[test]
+    fn panics_count_as_failures() {
+        let result = catch_unwind(|| check(|rng| rng.range(0..100), |&n| 10 / (n % 7) < 100));
+        assert!(result.is_err());
+
+        let (minimal, _) = shrink((3u64, 14u64), &|&(_, b): &(u64, u64)| 10 / (b % 7) < 100);
+        assert_eq!(minimal, (0, 0));
+    }
+}
@@ -0,0 +1,287 @@
+//! Drawings of puzzles, to see what a solver makes of its input.
+//!
+//! Days that can be drawn implement [`crate::Solution::render`], which paints the puzzle onto a
+//! [`Canvas`] of coloured characters. A canvas can be written as ANSI coloured text for the
+//! terminal, or as a PPM or SVG image; see [`Format`].
+
+use crate::grid::Grid;
+use anyhow::{bail, Result};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Size of a cell in PPM images, in pixels.
+const PPM_SCALE: usize = 4;
+
+/// Size of a cell in SVG images.
+const SVG_CELL_WIDTH: usize = 10;
+const SVG_CELL_HEIGHT: usize = 16;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Color {
+    Plain,
+    Dim,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    /// The SGR parameter selecting this colour as the foreground.
+    fn ansi(self) -> u8 {
+        match self {
+            Self::Plain => 39,
+            Self::Dim => 90,
+            Self::Red => 31,
+            Self::Green => 32,
+            Self::Yellow => 33,
+            Self::Blue => 34,
+            Self::Magenta => 35,
+            Self::Cyan => 36,
+        }
+    }
+
+    fn rgb(self) -> [u8; 3] {
+        match self {
+            Self::Plain => [204, 204, 204],
+            Self::Dim => [80, 80, 80],
+            Self::Red => [205, 49, 49],
+            Self::Green => [13, 188, 121],
+            Self::Yellow => [229, 229, 16],
+            Self::Blue => [36, 114, 200],
+            Self::Magenta => [188, 63, 188],
+            Self::Cyan => [17, 168, 205],
+        }
+    }
+}
+
+/// A character in a colour.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Cell {
+    pub c: char,
+    pub color: Color,
+}
+
+impl Cell {
+    pub const fn new(c: char, color: Color) -> Self {
+        Self { c, color }
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.c)
+    }
+}
+
+/// A drawing of a puzzle. Its plain text is its [`Display`](fmt::Display) output.
+pub type Canvas = Grid<Cell>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// Text, coloured with ANSI escape codes.
+    Ansi,
+
+    /// A binary PPM image, with a coloured square per cell.
+    Ppm,
+
+    /// An SVG image of the coloured text.
+    Svg,
+}
+
+impl Format {
+    /// The extension of files in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Ansi => "txt",
+            Self::Ppm => "ppm",
+            Self::Svg => "svg",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "ansi" => Ok(Self::Ansi),
+            "ppm" => Ok(Self::Ppm),
+            "svg" => Ok(Self::Svg),
+            other => bail!("unknown render format: {other}"),
+        }
+    }
+}
+
+/// Write `canvas` to `w` in `format`.
+pub fn write<W>(mut w: W, canvas: &Canvas, format: Format) -> io::Result<()>
+where
+    W: Write,
+{
+    match format {
+        Format::Ansi => write_ansi(&mut w, canvas),
+        Format::Ppm => write_ppm(&mut w, canvas),
+        Format::Svg => write_svg(&mut w, canvas),
+    }
+}
+
+fn write_ansi(w: &mut dyn Write, canvas: &Canvas) -> io::Result<()> {
+    for row in canvas.rows() {
+        let mut color = None;
+
+        for cell in row {
+            // only switch colours where they change
+            if color != Some(cell.color) {
+                write!(w, "\x1b[{}m", cell.color.ansi())?;
+                color = Some(cell.color);
+            }
+
+            write!(w, "{}", cell.c)?;
+        }
+
+        writeln!(w, "\x1b[0m")?;
+    }
+
+    Ok(())
+}
+
+fn write_ppm(w: &mut dyn Write, canvas: &Canvas) -> io::Result<()> {
+    let width = canvas.width() * PPM_SCALE;
+    let height = canvas.height() * PPM_SCALE;
+
+    write!(w, "P6\n{width} {height}\n255\n")?;
+
+    let mut line = Vec::with_capacity(width * 3);
+
+    for row in canvas.rows() {
+        line.clear();
+
+        for cell in row {
+            for _ in 0..PPM_SCALE {
+                line.extend(cell.color.rgb());
+            }
+        }
+
+        for _ in 0..PPM_SCALE {
+            w.write_all(&line)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_svg(w: &mut dyn Write, canvas: &Canvas) -> io::Result<()> {
+    let width = canvas.width() * SVG_CELL_WIDTH;
+    let height = canvas.height() * SVG_CELL_HEIGHT;
+
+    writeln!(
+        w,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )?;
+    writeln!(w, r#"<rect width="100%" height="100%" fill="black"/>"#)?;
+    writeln!(
+        w,
+        r#"<g font-family="monospace" font-size="{SVG_CELL_HEIGHT}" xml:space="preserve">"#
+    )?;
+
+    for (y, row) in canvas.rows().enumerate() {
+        // baseline of the text, leaving room for descenders
+        let baseline = (y + 1) * SVG_CELL_HEIGHT - SVG_CELL_HEIGHT / 4;
+
+        let mut x = 0;
+
+        // one text element per run of cells of the same colour
+        for run in row.chunk_by(|a, b| a.color == b.color) {
+            let [r, g, b] = run[0].color.rgb();
+
+            let mut text = String::new();
+
+            for cell in run {
+                match cell.c {
+                    '&' => text.push_str("&amp;"),
+                    '<' => text.push_str("&lt;"),
+                    '>' => text.push_str("&gt;"),
+                    c => text.push(c),
+                }
+            }
+
+            writeln!(
+                w,
+                r##"<text x="{}" y="{baseline}" textLength="{}" fill="#{r:02x}{g:02x}{b:02x}">{text}</text>"##,
+                x * SVG_CELL_WIDTH,
+                run.len() * SVG_CELL_WIDTH,
+            )?;
+
+            x += run.len();
+        }
+    }
+
+    writeln!(w, "</g>")?;
+    writeln!(w, "</svg>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canvas() -> Canvas {
+        let cells = [
+            ('#', Color::Green),
+            ('#', Color::Green),
+            ('<', Color::Red),
+            ('.', Color::Dim),
+            ('.', Color::Dim),
+            ('&', Color::Dim),
+        ];
+
+        Grid::new(3, 2, cells.map(|(c, color)| Cell::new(c, color)).to_vec())
+    }
+
+    fn written(format: Format) -> Vec<u8> {
+        let mut out = Vec::new();
+        write(&mut out, &canvas(), format).unwrap();
+        out
+    }
+
+    #[test]
+    fn ansi() {
+        let text = String::from_utf8(written(Format::Ansi)).unwrap();
+        assert_eq!(text, "\x1b[32m##\x1b[31m<\x1b[0m\n\x1b[90m..&\x1b[0m\n");
+
+        assert_eq!(canvas().to_string(), "##<\n..&\n");
+    }
+
+    #[test]
+    fn ppm() {
+        let image = written(Format::Ppm);
+        let header = b"P6\n12 8\n255\n";
+
+        assert!(image.starts_with(header));
+        assert_eq!(image.len(), header.len() + 12 * 8 * 3);
+
+        // the first pixel is green, the last one dim
+        assert_eq!(image[header.len()..][..3], Color::Green.rgb());
+        assert_eq!(image[image.len() - 3..], Color::Dim.rgb());
+    }
+
+    #[test]
+    fn svg() {
+        let image = String::from_utf8(written(Format::Svg)).unwrap();
+
+        assert!(image.starts_with("<svg "));
+        assert!(image.trim_end().ends_with("</svg>"));
+        assert_eq!(image.matches("<text ").count(), 3);
+        assert!(image.contains(">&lt;</text>"));
+        assert!(image.contains(r##"x="20" y="12" textLength="10" fill="#cd3131">"##));
+        assert!(image.contains(">..&amp;</text>"));
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!("svg".parse::<Format>().unwrap(), Format::Svg);
+        assert!("png".parse::<Format>().is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! Solving puzzles line by line, for inputs too big to keep in memory.
+//!
+//! Some puzzles are sums over lines that can be solved on their own. Their days implement
+//! [`Streaming`](crate::Streaming) by folding over the lines of the input with [`fold_lines`],
+//! keeping nothing but running totals. Both parts are solved in the same pass, so the input only
+//! has to be read once.
+
+use anyhow::{anyhow, Result};
+use std::io::BufRead;
+
+/// Fold `f` over the lines of `reader`, reading them one at a time into the same buffer.
+///
+/// Like [`BufRead::lines`], lines end with `\n` or `\r\n`, which `f` doesn't get to see. `f` is
+/// also given the number of each line, starting at 1, for its errors.
+pub fn fold_lines<R, T, F>(mut reader: R, init: T, mut f: F) -> Result<T>
+where
+    R: BufRead,
+    F: FnMut(T, usize, &str) -> Result<T>,
+{
+    let mut buf = String::new();
+    let mut acc = init;
+    let mut line_no = 0;
+
+    loop {
+        buf.clear();
+
+        if reader.read_line(&mut buf)? == 0 {
+            return Ok(acc);
+        }
+
+        line_no += 1;
+
+        let line = match buf.strip_suffix('\n') {
+            Some(line) => line.strip_suffix('\r').unwrap_or(line),
+            None => &buf,
+        };
+
+        acc = f(acc, line_no, line)?;
+    }
+}
+
+/// Split the answers to both parts, found at once, into an answer for each part.
+///
+/// If they couldn't be found, both parts fail with the same error.
+pub fn split<A, B>(result: Result<(A, B)>) -> (Result<A>, Result<B>) {
+    match result {
+        Ok((a, b)) => (Ok(a), Ok(b)),
+        Err(e) => (Err(anyhow!("{e:#}")), Err(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{self, Knobs};
+    use crate::{Part, DAYS};
+
+    #[test]
+    fn lines() {
+        let input = "one\r\ntwo\n\nfour";
+
+        let lines = fold_lines(input.as_bytes(), Vec::new(), |mut lines, n, line| {
+            lines.push((n, line.to_owned()));
+            Ok(lines)
+        })
+        .unwrap();
+
+        let expected = [(1, "one"), (2, "two"), (3, ""), (4, "four")];
+        assert_eq!(lines, expected.map(|(n, line)| (n, line.to_owned())));
+
+        let e = fold_lines("a\nb\n".as_bytes(), (), |_, n, _| {
+            anyhow::ensure!(n < 2, "line {n}");
+            Ok(())
+        });
+        assert_eq!(e.unwrap_err().to_string(), "line 2");
+    }
+
+    #[test]
+    fn streaming_agrees_with_parsing() {
+        for day in DAYS.iter().filter(|day| day.can_stream()) {
+            let generator = generate::generator(day.number).unwrap();
+            let mut knobs = Knobs::new();
+
+            if day.number == 12 {
+                // part one tries every arrangement, which is slow for long records in debug builds
+                knobs.set("length", 12);
+            }
+
+            for seed in 0..3 {
+                let input = generator.generate(seed, &knobs).unwrap();
+                let puzzle = day.parse(input.as_bytes()).unwrap();
+                let streamed = day.stream(input.as_bytes()).unwrap();
+
+                // parts that aren't solved must fail either way
+                for (part, answer) in Part::BOTH.into_iter().zip(streamed) {
+                    assert_eq!(
+                        answer.map_err(|e| e.to_string()),
+                        puzzle.solve(part).map_err(|e| e.to_string()),
+                        "day {} part {part} seed {seed}",
+                        day.number
+                    );
+                }
+            }
+        }
+    }
+}
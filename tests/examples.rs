@@ -0,0 +1,181 @@
+//! Runs every example in `inputs/day<n>/examples/` through the day registry.
+//!
+//! This is a test without the default harness, so that each part of each example shows up as a
+//! test of its own, e.g. `day08/ghosts/part2`. Like the default harness, it takes a filter as
+//! argument and understands `--list`. Days that can be solved line by line are also checked to
+//! give the same answer that way, and every example is checked to give the same answer again
+//! after writing it back out with `Display`, and after mangling it like an editor on another
+//! machine might.
+
+use anyhow::{anyhow, ensure, Result};
+use aoc_2023::answers::Status;
+use aoc_2023::examples::{self, Example};
+use aoc_2023::Part;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// `input` with a byte order mark, `\r\n` line ends, trailing whitespace and blank lines at the
+/// end, which the days should all normalise away.
+fn mangle(input: &[u8]) -> Result<Vec<u8>> {
+    let input = std::str::from_utf8(input)?;
+
+    let mut mangled = String::from("\u{feff}");
+
+    for line in input.lines() {
+        mangled += line;
+        mangled += " \t\r\n";
+    }
+
+    mangled += "\r\n  \r\n";
+    Ok(mangled.into_bytes())
+}
+
+/// Run `part` of `example`, returning why it failed if it did.
+fn run(example: &Example, part: Part) -> Result<()> {
+    let day = aoc_2023::day(example.day).ok_or_else(|| anyhow!("no solution"))?;
+
+    let input = std::fs::read(&example.path)?;
+    let puzzle = day.parse(input.as_slice())?;
+    let answer = puzzle.solve(part)?;
+
+    match example.answers.check(part, &answer) {
+        Status::Pass => {}
+        Status::Fail { expected } => return Err(anyhow!("expected {expected}, got {answer}")),
+        Status::Missing => return Err(anyhow!("no answer recorded")),
+    }
+
+    let text = puzzle.to_string();
+    let reparsed = day.parse(text.as_bytes())?;
+
+    ensure!(
+        reparsed.to_string() == text,
+        "writing the example out again changed it"
+    );
+
+    let again = reparsed.solve(part)?;
+    ensure!(
+        again == answer,
+        "the example as written out gave {again}, not {answer}"
+    );
+
+    let mangled = mangle(&input)?;
+    let again = day.parse(mangled.as_slice())?.solve(part)?;
+    ensure!(
+        again == answer,
+        "the mangled example gave {again}, not {answer}"
+    );
+
+    if let Some([one, two]) = day.stream(mangled.as_slice()) {
+        let streamed = match part {
+            Part::One => one,
+            Part::Two => two,
+        }?;
+
+        ensure!(
+            streamed == answer,
+            "streaming gave {streamed}, not {answer}"
+        );
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let mut filters = Vec::new();
+    let mut list = false;
+    let mut ignored = false;
+
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--list" => list = true,
+            "--ignored" => ignored = true,
+
+            // options of the default harness that take a value
+            "--format" | "--test-threads" | "--color" | "--skip" | "-Z" => {
+                args.next();
+            }
+
+            flag if flag.starts_with('-') => {}
+            filter => filters.push(filter.to_owned()),
+        }
+    }
+
+    let inputs = Path::new(env!("CARGO_MANIFEST_DIR")).join("inputs");
+
+    let examples = match examples::discover(inputs) {
+        Ok(examples) => examples,
+        Err(e) => {
+            eprintln!("could not load examples: {e:#}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let tests = examples
+        .iter()
+        .flat_map(|example| example.parts().into_iter().map(move |part| (example, part)))
+        .collect::<Vec<_>>();
+
+    let total = tests.len();
+
+    // there are no ignored examples
+    let selected = tests
+        .into_iter()
+        .filter(|_| !ignored)
+        .filter(|(example, part)| {
+            let name = example.test_name(*part);
+            filters.is_empty() || filters.iter().any(|filter| name.contains(filter.as_str()))
+        })
+        .collect::<Vec<_>>();
+
+    if list {
+        for (example, part) in &selected {
+            println!("{}: test", example.test_name(*part));
+        }
+
+        return ExitCode::SUCCESS;
+    }
+
+    println!();
+    println!("running {} tests", selected.len());
+
+    let mut failures = Vec::new();
+
+    for (example, part) in &selected {
+        let name = example.test_name(*part);
+
+        match run(example, *part) {
+            Ok(()) => println!("test {name} ... ok"),
+            Err(e) => {
+                println!("test {name} ... FAILED");
+                failures.push((name, e));
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        println!();
+        println!("failures:");
+
+        for (name, e) in &failures {
+            println!("    {name}: {e:#}");
+        }
+    }
+
+    println!();
+    println!(
+        "test result: {}. {} passed; {} failed; 0 ignored; 0 measured; {} filtered out",
+        if failures.is_empty() { "ok" } else { "FAILED" },
+        selected.len() - failures.len(),
+        failures.len(),
+        total - selected.len(),
+    );
+    println!();
+
+    if failures.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}